@@ -0,0 +1,67 @@
+/*
+ * Example shared library strategy. Build it into the bots directory, where it is loaded at startup:
+ *   cc -shared -fPIC -o bots/libexample_bot.so bots/example_bot.c
+ *   robot_challenge_rust run -s example_bot,firefire,random
+ * Ignores its input and repeats fire, fire, turn right, forward.
+ *
+ * Threading: every tank in a game gets its own bot from create, and bots of different tanks or games run
+ * at the same time on different threads. One bot is only used by one thread at a time, but create, seed,
+ * next_move and destroy for it may each be called on a different thread. State shared between bots, such
+ * as globals, must be thread-safe. create returns NULL on failure, and the tank then faults every move.
+ */
+#include <stdint.h>
+#include <stdlib.h>
+
+/* Moves: 0 Fire, 1 TurnLeft, 2 Forward, 3 TurnRight, 4 Wait. */
+enum { FIRE, TURN_LEFT, FORWARD, TURN_RIGHT, WAIT };
+
+struct bot_descriptor {
+    uint32_t abi_version;
+    const char *name;
+    const char *author;
+    /* Returns NULL on failure. */
+    void *(*create)(void);
+    void (*destroy)(void *bot);
+    /* May be NULL. */
+    void (*seed)(void *bot, uint64_t seed);
+    /* The input is NextMoveInput as JSON of length len, not NUL terminated. */
+    int32_t (*next_move)(void *bot, const char *input, size_t len);
+};
+
+struct example_bot {
+    uint64_t turn;
+};
+
+static void *create(void) {
+    return calloc(1, sizeof(struct example_bot));
+}
+
+static void destroy(void *bot) {
+    free(bot);
+}
+
+static void seed(void *bot, uint64_t seed) {
+    ((struct example_bot *) bot)->turn = seed % 4;
+}
+
+static int32_t next_move(void *bot, const char *input, size_t len) {
+    (void) input;
+    (void) len;
+    static const int32_t moves[] = { FIRE, FIRE, TURN_RIGHT, FORWARD };
+    struct example_bot *example = bot;
+    return moves[example->turn++ % 4];
+}
+
+static const struct bot_descriptor descriptor = {
+    .abi_version = 1,
+    .name = "CBot",
+    .author = "Example",
+    .create = create,
+    .destroy = destroy,
+    .seed = seed,
+    .next_move = next_move,
+};
+
+const struct bot_descriptor *robot_challenge_bot_v1(void) {
+    return &descriptor;
+}
@@ -0,0 +1,45 @@
+#!/usr/bin/env python3
+# Example bot for the JSON lines protocol, run with:
+#   robot_challenge_rust run --bot "pybot=python3 bots/example_bot.py" -s pybot,firefire,random
+# Fires when an opponent is in line of fire, otherwise moves around randomly.
+import json
+import random
+import sys
+
+
+def in_line_of_fire(own, opponent, fire_range):
+    dx = opponent["location"]["x"] - own["location"]["x"]
+    dy = opponent["location"]["y"] - own["location"]["y"]
+    direction = own["direction"]
+    if direction == "North":
+        return dx == 0 and -fire_range <= dy < 0
+    if direction == "South":
+        return dx == 0 and 0 < dy <= fire_range
+    if direction == "East":
+        return dy == 0 and 0 < dx <= fire_range
+    return dy == 0 and -fire_range <= dx < 0
+
+
+def next_move(state):
+    own = state["own_status"]
+    for opponent in state["opponent_status"]:
+        if opponent["is_alive"] and in_line_of_fire(own, opponent, state["fire_range"]):
+            return "Fire"
+    return random.choice(["TurnLeft", "Forward", "Forward", "TurnRight"])
+
+
+def main():
+    for line in sys.stdin:
+        request = json.loads(line)
+        if request["type"] == "hello":
+            reply = {"name": "PyBot", "author": "Example"}
+        elif request["type"] == "seed":
+            random.seed(request["seed"])
+            reply = {}
+        else:
+            reply = {"move": next_move(request["input"])}
+        print(json.dumps(reply), flush=True)
+
+
+if __name__ == "__main__":
+    main()
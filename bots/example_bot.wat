@@ -0,0 +1,33 @@
+;; Example WebAssembly strategy, run with:
+;;   robot_challenge_rust run --wasm "watbot=bots/example_bot.wat" -s watbot,firefire,random
+;; Ignores its input and repeats fire, fire, turn right, forward.
+(module
+  (memory (export "memory") 1)
+  (global $turn (mut i32) (i32.const 0))
+  (data (i32.const 0) "WatBot")
+  (data (i32.const 16) "Example")
+  ;; Moves: 0 Fire, 1 TurnLeft, 2 Forward, 3 TurnRight, 4 Wait.
+  (data (i32.const 32) "\00\00\03\02")
+
+  ;; Strings are returned as offset << 32 | length.
+  (func (export "name") (result i64)
+    (i64.const 6))
+  (func (export "author") (result i64)
+    (i64.or (i64.shl (i64.const 16) (i64.const 32)) (i64.const 7)))
+
+  (func (export "seed") (param $seed i64)
+    (global.set $turn (i32.wrap_i64 (i64.rem_u (local.get $seed) (i64.const 4)))))
+
+  ;; The input is always written at offset 1024, memory grows when it doesn't fit.
+  (func (export "alloc") (param $len i32) (result i32)
+    (local $missing i32)
+    (local.set $missing
+      (i32.sub (i32.add (i32.const 1024) (local.get $len)) (i32.mul (memory.size) (i32.const 65536))))
+    (if (i32.gt_s (local.get $missing) (i32.const 0))
+      (then
+        (drop (memory.grow (i32.add (i32.div_u (local.get $missing) (i32.const 65536)) (i32.const 1))))))
+    (i32.const 1024))
+
+  (func (export "next_move") (param $ptr i32) (param $len i32) (result i32)
+    (global.set $turn (i32.rem_u (i32.add (global.get $turn) (i32.const 1)) (i32.const 4)))
+    (i32.load8_u (i32.add (i32.const 32) (global.get $turn)))))
@@ -0,0 +1,557 @@
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+use clap::{
+    Args,
+    CommandFactory,
+    FromArgMatches,
+    Parser,
+    Subcommand
+};
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use iced::{
+    Application,
+    Settings
+};
+use crate::compare::Comparison;
+use crate::config::{
+    ConfigError,
+    MatchConfig,
+    RosterEntry
+};
+use crate::dylib;
+use crate::engine::{
+    FaultPolicy,
+    TieBreak
+};
+use crate::game::{
+    GameSource,
+    GuiFlags,
+    RobotChallenge
+};
+use crate::process;
+use crate::ratings::Ratings;
+use crate::registry::{
+    Registry,
+    StrategyKind
+};
+use crate::remote;
+use crate::replay::{
+    Recorder,
+    Replay
+};
+use crate::script;
+use crate::tournament::{
+    Format,
+    Tournament
+};
+use crate::wasm;
+use crate::wasm::WasmLimits;
+
+
+// Move time limit in the GUI for bot processes and remote bots, when the match has none.
+const GUI_MOVE_TIME_LIMIT_MS: u64 = 1000;
+
+#[derive(Parser, Debug)]
+#[command(about = "Robot Challenge tank battles")]
+pub(crate) struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Add a bot running as an external process, speaking JSON lines on stdin/stdout
+    #[arg(long = "bot", value_name = "ID=COMMAND", global = true)]
+    bots: Vec<String>,
+
+    /// Add a sandboxed WebAssembly strategy from a .wasm or .wat file
+    #[arg(long = "wasm", value_name = "ID=PATH", global = true)]
+    wasm_plugins: Vec<String>,
+
+    /// Fuel for every call into a WebAssembly strategy, roughly its instruction count [default: 10000000]
+    #[arg(long, global = true)]
+    wasm_fuel: Option<u64>,
+
+    /// Memory limit of a WebAssembly strategy in MiB [default: 16]
+    #[arg(long, global = true)]
+    wasm_memory_mib: Option<usize>,
+
+    /// Add a Rhai script strategy, reloaded whenever the file changes
+    #[arg(long = "script", value_name = "ID=PATH", global = true)]
+    scripts: Vec<String>,
+
+    /// Listen on this address for bots connecting over TCP before starting, e.g. 127.0.0.1:7878
+    #[arg(long, value_name = "ADDRESS", global = true)]
+    listen: Option<String>,
+
+    /// Number of remote bots to wait for with --listen, registered as remote1, remote2...
+    #[arg(long, default_value_t = 1, global = true)]
+    remote_bots: usize,
+
+    /// Directory with shared library strategies, loaded at startup
+    #[arg(long, value_name = "DIR", default_value = "bots", global = true)]
+    bots_dir: PathBuf,
+
+    #[command(flatten)]
+    gui: GuiArgs,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Watch a match in the GUI (default)
+    Gui(GuiArgs),
+    /// Run a single match without the GUI
+    Run(RunArgs),
+    /// Run a tournament between the strategies without the GUI and print the standings
+    Tournament(TournamentArgs),
+    /// Play strategy A against strategy B on the same seeds and report whether one is better
+    Compare(CompareArgs),
+    /// List the available strategies
+    ListStrategies(ListStrategiesArgs),
+    /// Print the strategy ratings
+    Leaderboard(LeaderboardArgs),
+    /// Watch a recorded match in the GUI
+    Replay(ReplayArgs),
+    /// Play a strategy as a remote bot in a game started with --listen
+    Connect(ConnectArgs),
+}
+
+#[derive(Args, Debug)]
+struct MatchArgs {
+    /// Match file (TOML, or JSON with a .json extension). Other options override it
+    #[arg(short, long)]
+    config: Option<PathBuf>,
+
+    /// Strategy ids or names, one per tank
+    #[arg(short, long = "strategy", value_delimiter = ',')]
+    strategies: Vec<String>,
+
+    /// Board width [default: 20]
+    #[arg(long)]
+    width: Option<usize>,
+
+    /// Board height [default: 20]
+    #[arg(long)]
+    height: Option<usize>,
+
+    /// [default: 100]
+    #[arg(long)]
+    max_rounds: Option<usize>,
+
+    /// Start energy of every tank [default: 5]
+    #[arg(long)]
+    energy: Option<usize>,
+
+    /// [default: 5]
+    #[arg(long)]
+    fire_range: Option<usize>,
+
+    /// Tie-breaks between survivors when max rounds is reached, in order [default: energy,frags,hits]
+    #[arg(long, value_delimiter = ',')]
+    tie_breaks: Option<Vec<TieBreak>>,
+
+    /// Time a strategy may take for one move, in milliseconds [default: no limit, in the GUI 1000 with a bot
+    /// process or remote bot]
+    #[arg(long)]
+    move_time_limit_ms: Option<u64>,
+
+    /// Time a strategy may take for all its moves in a match, in milliseconds [default: no limit]
+    #[arg(long)]
+    match_time_limit_ms: Option<u64>,
+
+    /// What happens when a strategy is too slow or panics: wait, lose-energy or disqualify [default: wait]
+    #[arg(long)]
+    fault_policy: Option<FaultPolicy>,
+
+    /// Strategies only see opponents within this many squares [default: the whole board]
+    #[arg(long)]
+    vision_radius: Option<usize>,
+
+    /// Strategies only see opponents in a straight line that no other tank hides
+    #[arg(long)]
+    line_of_sight: bool,
+
+    /// Match seed, random if not set
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+#[derive(Args, Debug)]
+struct GuiArgs {
+    #[command(flatten)]
+    match_args: MatchArgs,
+
+    /// Delay between animation steps in milliseconds
+    #[arg(long, default_value_t = 100)]
+    delay_ms: u64,
+
+    /// Write a replay of the match to this file, rewritten when a new game is started
+    #[arg(long)]
+    record: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct RunArgs {
+    #[command(flatten)]
+    match_args: MatchArgs,
+
+    /// Write a replay of the match to this file
+    #[arg(long)]
+    record: Option<PathBuf>,
+
+    /// Update the strategy ratings in this file with the result
+    #[arg(long)]
+    ratings: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct TournamentArgs {
+    #[command(flatten)]
+    match_args: MatchArgs,
+
+    /// free-for-all, round-robin or swiss
+    #[arg(long, default_value_t = Format::FreeForAll)]
+    format: Format,
+
+    /// Number of games per pairing, seeded from the match seed upwards
+    #[arg(short, long, default_value_t = 10)]
+    games: u64,
+
+    /// Number of swiss rounds [default: enough to separate the strategies]
+    #[arg(long)]
+    swiss_rounds: Option<usize>,
+
+    /// Number of games played in parallel [default: number of CPUs]
+    #[arg(long)]
+    threads: Option<usize>,
+
+    /// Write a replay of every game to this directory, named by game number and seed
+    #[arg(long)]
+    record_dir: Option<PathBuf>,
+
+    /// Update the strategy ratings in this file with the results
+    #[arg(long)]
+    ratings: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct CompareArgs {
+    /// Strategy id or name of A
+    a: String,
+
+    /// Strategy id or name of B
+    b: String,
+
+    /// Rules and seed of the games. The tanks are always A and B
+    #[command(flatten)]
+    match_args: MatchArgs,
+
+    /// Number of seeds, every seed is played twice with the start positions swapped
+    #[arg(short, long, default_value_t = 100)]
+    games: u64,
+
+    /// Number of games played in parallel [default: number of CPUs]
+    #[arg(long)]
+    threads: Option<usize>,
+}
+
+#[derive(Args, Debug)]
+struct ListStrategiesArgs {
+    /// Only list strategies by this author
+    #[arg(long)]
+    author: Option<String>,
+}
+
+#[derive(Args, Debug)]
+struct ConnectArgs {
+    /// Address the game listens on
+    address: String,
+
+    /// Strategy id or name to play
+    #[arg(short, long)]
+    strategy: String,
+}
+
+#[derive(Args, Debug)]
+struct LeaderboardArgs {
+    /// Ratings file written with --ratings
+    #[arg(default_value = "ratings.json")]
+    ratings: PathBuf,
+}
+
+#[derive(Args, Debug)]
+struct ReplayArgs {
+    /// Replay file written with --record
+    file: PathBuf,
+
+    /// Delay between frames when playing, in milliseconds
+    #[arg(long, default_value_t = 100)]
+    delay_ms: u64,
+}
+
+impl MatchArgs {
+    fn match_config(&self) -> Result<MatchConfig, ConfigError> {
+        let mut config = match &self.config {
+            Some(path) => MatchConfig::load(path)?,
+            None => MatchConfig::default(),
+        };
+        let rules = &mut config.rules;
+        rules.dimension.width = self.width.unwrap_or(rules.dimension.width);
+        rules.dimension.height = self.height.unwrap_or(rules.dimension.height);
+        rules.max_rounds = self.max_rounds.unwrap_or(rules.max_rounds);
+        rules.max_energy = self.energy.unwrap_or(rules.max_energy);
+        rules.fire_range = self.fire_range.unwrap_or(rules.fire_range);
+        if let Some(tie_breaks) = &self.tie_breaks {
+            rules.tie_breaks = tie_breaks.clone();
+        }
+        rules.move_time_limit_ms = self.move_time_limit_ms.or(rules.move_time_limit_ms);
+        rules.match_time_limit_ms = self.match_time_limit_ms.or(rules.match_time_limit_ms);
+        rules.fault_policy = self.fault_policy.unwrap_or(rules.fault_policy);
+        rules.vision_radius = self.vision_radius.or(rules.vision_radius);
+        rules.line_of_sight |= self.line_of_sight;
+        if !self.strategies.is_empty() {
+            config.roster = self.strategies.iter().map(|id| RosterEntry::new(id)).collect();
+        }
+        if let Some(seed) = self.seed {
+            config.seed = seed;
+        }
+        Ok(config)
+    }
+}
+
+impl Cli {
+    // The global options can go before or after the subcommand. The GUI options at the top level are only for
+    // running without a subcommand, clap would otherwise ignore them silently.
+    pub(crate) fn parse_args() -> Self {
+        let mut command = Self::command();
+        let matches = command.get_matches_mut();
+        if matches.subcommand_name().is_some() {
+            let gui_option = command.get_arguments()
+                .filter(|arg| !arg.is_global_set())
+                .find(|arg| matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine))
+                .map(|arg| arg.get_long().map_or_else(|| arg.get_id().to_string(), |long| format!("--{}", long)));
+            if let Some(option) = gui_option {
+                command.error(ErrorKind::ArgumentConflict, format!("{} must come after the subcommand", option)).exit();
+            }
+        }
+        Self::from_arg_matches(&matches).unwrap_or_else(|error| error.exit())
+    }
+
+    pub(crate) fn run(self) -> Result<(), Box<dyn Error>> {
+        let mut registry = Registry::builtin();
+        dylib::register_dir(&mut registry, &self.bots_dir);
+        for bot in &self.bots {
+            process::register(&mut registry, bot)?;
+        }
+        let defaults = WasmLimits::default();
+        let wasm_limits = WasmLimits {
+            fuel: self.wasm_fuel.unwrap_or(defaults.fuel),
+            memory_bytes: self.wasm_memory_mib.map_or(defaults.memory_bytes, |mib| mib * 1024 * 1024),
+        };
+        for plugin in &self.wasm_plugins {
+            wasm::register(&mut registry, plugin, wasm_limits)?;
+        }
+        for script in &self.scripts {
+            script::register(&mut registry, script)?;
+        }
+        if let Some(address) = &self.listen {
+            remote::accept(&mut registry, address, self.remote_bots)?;
+        }
+        match self.command {
+            None => run_gui(self.gui, registry),
+            Some(Command::Gui(args)) => run_gui(args, registry),
+            Some(Command::Run(args)) => run_match(args, &registry),
+            Some(Command::Tournament(args)) => run_tournament(args, &registry),
+            Some(Command::Compare(args)) => run_compare(args, &registry),
+            Some(Command::ListStrategies(args)) => {
+                list_strategies(args, &registry);
+                Ok(())
+            }
+            Some(Command::Leaderboard(args)) => leaderboard(args),
+            Some(Command::Replay(args)) => run_replay(args),
+            Some(Command::Connect(args)) => run_connect(args, &registry),
+        }
+    }
+}
+
+fn list_strategies(args: ListStrategiesArgs, registry: &Registry) {
+    let entries: Vec<_> = match &args.author {
+        Some(author) => registry.by_author(author).collect(),
+        None => registry.entries().iter().collect(),
+    };
+    println!("{:<12} {:<12} {:<10} Description", "Id", "Name", "Author");
+    for entry in entries {
+        println!("{:<12} {:<12} {:<10} {}", entry.id, entry.name, entry.author, entry.description);
+    }
+}
+
+fn run_connect(args: ConnectArgs, registry: &Registry) -> Result<(), Box<dyn Error>> {
+    let entry = registry.lookup(&args.strategy).ok_or_else(|| format!("unknown strategy '{}'", args.strategy))?;
+    remote::connect(&args.address, entry)?;
+    Ok(())
+}
+
+fn leaderboard(args: LeaderboardArgs) -> Result<(), Box<dyn Error>> {
+    let ratings = Ratings::load(&args.ratings)?;
+    print_leaderboard(&ratings);
+    Ok(())
+}
+
+fn print_leaderboard(ratings: &Ratings) {
+    println!("{:<4} {:<20} {:>7} {:>6}", "", "Strategy", "Rating", "Games");
+    for (rank, (id, rating)) in ratings.leaderboard().into_iter().enumerate() {
+        println!("{:<4} {:<20} {:>7.1} {:>6}", rank + 1, id, rating.rating, rating.games);
+    }
+}
+
+fn run_gui(args: GuiArgs, registry: Registry) -> Result<(), Box<dyn Error>> {
+    let mut config = args.match_args.match_config()?;
+    // The GUI waits for every move, a bot process or remote bot that stops answering would freeze it.
+    let has_bots = registry.kinds(config.roster.iter().map(|entry| &entry.strategy)).any(|kind| kind != StrategyKind::Local);
+    if config.rules.move_time_limit_ms.is_none() && has_bots {
+        println!("Using a move time limit of {} ms for the bots", GUI_MOVE_TIME_LIMIT_MS);
+        config.rules.move_time_limit_ms = Some(GUI_MOVE_TIME_LIMIT_MS);
+    }
+    // Fail before opening a window if the match can't be created.
+    config.validate(&registry)?;
+    let flags = GuiFlags {
+        source: GameSource::Live {
+            config,
+            registry,
+            record: args.record,
+        },
+        step_delay: Duration::from_millis(args.delay_ms),
+    };
+    RobotChallenge::run(Settings::with_flags(flags))?;
+    Ok(())
+}
+
+fn run_replay(args: ReplayArgs) -> Result<(), Box<dyn Error>> {
+    let replay = Replay::load(&args.file)?;
+    let flags = GuiFlags {
+        source: GameSource::Replay(replay),
+        step_delay: Duration::from_millis(args.delay_ms),
+    };
+    RobotChallenge::run(Settings::with_flags(flags))?;
+    Ok(())
+}
+
+fn run_match(args: RunArgs, registry: &Registry) -> Result<(), Box<dyn Error>> {
+    let config = args.match_args.match_config()?;
+    let mut engine = config.create_engine(registry)?;
+    println!("Seed {}", engine.seed());
+    match &args.record {
+        Some(path) => Recorder::create(path, &config, &engine)?.run_to_completion(&mut engine)?,
+        None => engine.run_to_completion(),
+    };
+    let result = engine.result().unwrap();
+    let tanks = engine.tanks();
+    println!("{}", result.summary(|index| format!("{} by {}", tanks[index].strategy.name(), tanks[index].strategy.author())));
+    println!("{:<6} {:<20} {:>6} {:>5} {:>5}  Eliminated", "Place", "Name", "Energy", "Hits", "Frags");
+    for placement in &result.ranking {
+        let tank = &tanks[placement.tank];
+        let eliminated = match placement.eliminated {
+            Some(elimination) => {
+                let by = match elimination.by {
+                    Some(by) => tanks[by].strategy.name(),
+                    None => "fault".to_string(),
+                };
+                format!("round {}, move {}, by {}", elimination.round, elimination.move_number, by)
+            }
+            None => String::new(),
+        };
+        println!("{:<6} {:<20} {:>6} {:>5} {:>5}  {}", placement.place, tank.strategy.name(), tank.energy, tank.hits,
+                 tank.frags, eliminated);
+    }
+
+    if let Some(path) = &args.ratings {
+        let mut ratings = Ratings::load(path)?;
+        let placements: Vec<(&str, usize)> = result.ranking.iter()
+            .map(|placement| {
+                let entry = &config.roster[placement.tank];
+                (registry.lookup(&entry.strategy).unwrap().id.as_str(), placement.place)
+            })
+            .collect();
+        ratings.update(&placements);
+        ratings.save(path)?;
+        println!();
+        print_leaderboard(&ratings);
+    }
+    Ok(())
+}
+
+fn run_tournament(args: TournamentArgs, registry: &Registry) -> Result<(), Box<dyn Error>> {
+    let config = args.match_args.match_config()?;
+    println!("Seed {}", config.seed);
+    if let Some(dir) = &args.record_dir {
+        fs::create_dir_all(dir)?;
+    }
+
+    let tournament = Tournament {
+        registry,
+        config,
+        format: args.format,
+        games: args.games,
+        swiss_rounds: args.swiss_rounds,
+        threads: args.threads.unwrap_or_else(default_threads),
+        record_dir: args.record_dir,
+    };
+    let mut ratings = args.ratings.as_deref().map(Ratings::load).transpose()?;
+    let standings = tournament.run(ratings.as_mut())?;
+
+    println!("{:<4} {:<20} {:>5} {:>5} {:>5} {:>9} {:>5} {:>5}", "", "Strategy", "Games", "Wins", "Draws", "Avg place",
+             "Frags", "Hits");
+    for (rank, standing) in standings.iter().enumerate() {
+        println!("{:<4} {:<20} {:>5} {:>5} {:>5} {:>9.2} {:>5} {:>5}", rank + 1, standing.strategy, standing.games,
+                 standing.wins, standing.draws, standing.average_placement(), standing.frags, standing.hits);
+    }
+
+    if let (Some(path), Some(ratings)) = (&args.ratings, &ratings) {
+        ratings.save(path)?;
+        println!();
+        print_leaderboard(ratings);
+    }
+    Ok(())
+}
+
+fn run_compare(args: CompareArgs, registry: &Registry) -> Result<(), Box<dyn Error>> {
+    if !args.match_args.strategies.is_empty() {
+        return Err("compare plays A against B, --strategy can't be used".into());
+    }
+    let config = args.match_args.match_config()?;
+    println!("Seed {}", config.seed);
+
+    let comparison = Comparison {
+        registry,
+        rules: config.rules,
+        a: args.a,
+        b: args.b,
+        seeds: args.games,
+        first_seed: config.seed,
+        threads: args.threads.unwrap_or_else(default_threads),
+    };
+    let report = comparison.run()?;
+
+    println!("{} games, every seed played with swapped start positions", report.games);
+    println!("{:<4} {:<20} {:>5} {:>11}", "", "Strategy", "Wins", "Energy left");
+    println!("{:<4} {:<20} {:>5} {:>11.2}", "A", comparison.a, report.a_wins, report.a_average_energy());
+    println!("{:<4} {:<20} {:>5} {:>11.2}", "B", comparison.b, report.b_wins, report.b_average_energy());
+    println!("{:<4} {:<20} {:>5}", "", "(draw)", report.draws);
+    let (low, high) = report.a_score_interval();
+    println!("A score {:.1}% (95% confidence interval {:.1}% to {:.1}%), draws count half",
+             report.a_score() * 100.0, low * 100.0, high * 100.0);
+    println!("Average game length {:.1} rounds", report.average_rounds());
+    if low > 0.5 {
+        println!("{} is better than {}", comparison.a, comparison.b);
+    } else if high < 0.5 {
+        println!("{} is better than {}", comparison.b, comparison.a);
+    } else {
+        println!("No significant difference, try more games");
+    }
+    Ok(())
+}
+
+fn default_threads() -> usize {
+    thread::available_parallelism().map_or(1, |threads| threads.get())
+}
@@ -0,0 +1,206 @@
+use crate::config::{
+    ConfigError,
+    MatchConfig,
+    RosterEntry
+};
+use crate::engine::Rules;
+use crate::registry::{
+    Registry,
+    StrategyKind
+};
+use crate::tournament::play_parallel;
+
+
+// Plays strategy A against strategy B one-on-one. Every seed is played twice with the tanks swapped, so both
+// strategies get the same start positions, turn order and strategy seeds.
+pub(crate) struct Comparison<'a> {
+    pub(crate) registry: &'a Registry,
+    pub(crate) rules: Rules,
+    pub(crate) a: String,
+    pub(crate) b: String,
+    // Number of seeds, seeded from first_seed upwards. Twice as many games are played.
+    pub(crate) seeds: u64,
+    pub(crate) first_seed: u64,
+    pub(crate) threads: usize,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct ComparisonReport {
+    pub(crate) games: usize,
+    pub(crate) a_wins: usize,
+    pub(crate) b_wins: usize,
+    pub(crate) draws: usize,
+    a_energy: usize,
+    b_energy: usize,
+    rounds: usize,
+}
+
+struct Game {
+    seed: u64,
+    a_first: bool,
+}
+
+struct GameOutcome {
+    a_won: bool,
+    b_won: bool,
+    a_energy: usize,
+    b_energy: usize,
+    rounds: usize,
+}
+
+impl Comparison<'_> {
+    pub(crate) fn run(&self) -> Result<ComparisonReport, ConfigError> {
+        // Fail once up front instead of in every game.
+        self.config(self.first_seed, true).validate(self.registry)?;
+
+        let games: Vec<Game> = (0..self.seeds)
+            .flat_map(|game| {
+                let seed = self.first_seed.wrapping_add(game);
+                [Game { seed, a_first: true }, Game { seed, a_first: false }]
+            })
+            .collect();
+        // A remote bot plays one game at a time.
+        let threads = if self.registry.kinds([&self.a, &self.b]).any(|kind| kind == StrategyKind::Remote) {
+            1
+        } else {
+            self.threads
+        };
+        let outcomes = play_parallel(&games, threads, |game| self.play_game(game));
+
+        let mut report = ComparisonReport::default();
+        for outcome in outcomes {
+            let outcome = outcome?;
+            report.games += 1;
+            if outcome.a_won {
+                report.a_wins += 1;
+            } else if outcome.b_won {
+                report.b_wins += 1;
+            } else {
+                report.draws += 1;
+            }
+            report.a_energy += outcome.a_energy;
+            report.b_energy += outcome.b_energy;
+            report.rounds += outcome.rounds;
+        }
+        Ok(report)
+    }
+
+    fn config(&self, seed: u64, a_first: bool) -> MatchConfig {
+        let (first, second) = if a_first { (&self.a, &self.b) } else { (&self.b, &self.a) };
+        MatchConfig {
+            rules: self.rules.clone(),
+            roster: vec![RosterEntry::new(first), RosterEntry::new(second)],
+            seed,
+        }
+    }
+
+    fn play_game(&self, game: &Game) -> Result<GameOutcome, ConfigError> {
+        let mut engine = self.config(game.seed, game.a_first).create_engine(self.registry)?;
+        let winner = engine.run_to_completion();
+        let (a, b) = if game.a_first { (0, 1) } else { (1, 0) };
+        Ok(GameOutcome {
+            a_won: winner == Some(a),
+            b_won: winner == Some(b),
+            a_energy: engine.tanks()[a].energy,
+            b_energy: engine.tanks()[b].energy,
+            rounds: engine.result().unwrap().round,
+        })
+    }
+}
+
+impl ComparisonReport {
+    // 95% confidence.
+    const Z: f64 = 1.96;
+
+    // Share of the games A won, counting draws as half a win.
+    pub(crate) fn a_score(&self) -> f64 {
+        self.ratio(self.a_wins as f64 + self.draws as f64 / 2.0)
+    }
+
+    // Wilson score interval of the A score.
+    pub(crate) fn a_score_interval(&self) -> (f64, f64) {
+        if self.games == 0 {
+            return (0.0, 1.0);
+        }
+        let n = self.games as f64;
+        let p = self.a_score();
+        let z2 = Self::Z * Self::Z;
+        let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+        let margin = Self::Z / (1.0 + z2 / n) * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+        ((center - margin).max(0.0), (center + margin).min(1.0))
+    }
+
+    pub(crate) fn a_average_energy(&self) -> f64 {
+        self.ratio(self.a_energy as f64)
+    }
+
+    pub(crate) fn b_average_energy(&self) -> f64 {
+        self.ratio(self.b_energy as f64)
+    }
+
+    pub(crate) fn average_rounds(&self) -> f64 {
+        self.ratio(self.rounds as f64)
+    }
+
+    fn ratio(&self, value: f64) -> f64 {
+        if self.games == 0 {
+            return 0.0;
+        }
+        value / self.games as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(a_wins: usize, b_wins: usize, draws: usize) -> ComparisonReport {
+        ComparisonReport {
+            games: a_wins + b_wins + draws,
+            a_wins,
+            b_wins,
+            draws,
+            ..ComparisonReport::default()
+        }
+    }
+
+    fn assert_interval(report: &ComparisonReport, low: f64, high: f64) {
+        let (actual_low, actual_high) = report.a_score_interval();
+        assert!((actual_low - low).abs() < 1e-4 && (actual_high - high).abs() < 1e-4,
+                "expected ({}, {}), got ({}, {})", low, high, actual_low, actual_high);
+    }
+
+    #[test]
+    fn even_score_interval_is_symmetric() {
+        assert_interval(&report(5, 5, 0), 0.2366, 0.7634);
+    }
+
+    #[test]
+    fn draws_count_half() {
+        let draws = report(0, 0, 10);
+        assert_eq!(draws.a_score(), 0.5);
+        assert_interval(&draws, 0.2366, 0.7634);
+        assert_eq!(report(3, 1, 2).a_score(), 4.0 / 6.0);
+    }
+
+    #[test]
+    fn interval_stays_within_zero_and_one() {
+        assert_interval(&report(10, 0, 0), 0.7225, 1.0);
+        assert_interval(&report(0, 10, 0), 0.0, 0.2775);
+    }
+
+    #[test]
+    fn interval_narrows_with_more_games() {
+        assert_interval(&report(70, 30, 0), 0.6041, 0.7811);
+        let width = |report: ComparisonReport| {
+            let (low, high) = report.a_score_interval();
+            high - low
+        };
+        assert!(width(report(70, 30, 0)) < width(report(7, 3, 0)));
+    }
+
+    #[test]
+    fn no_games_is_the_whole_range() {
+        assert_eq!(report(0, 0, 0).a_score_interval(), (0.0, 1.0));
+    }
+}
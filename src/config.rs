@@ -0,0 +1,303 @@
+use std::fmt::{
+    Display,
+    Formatter
+};
+use std::fs;
+use std::path::{
+    Path,
+    PathBuf
+};
+use serde::Deserialize;
+use crate::engine::{
+    BoardPoint,
+    Dimension,
+    Direction,
+    Engine,
+    FaultPolicy,
+    Rules,
+    TankSetup,
+    TieBreak
+};
+use crate::registry::{
+    Registry,
+    StrategyKind
+};
+use crate::strategies;
+
+
+// Largest board width and height, positions are computed with isize.
+const MAX_BOARD_SIZE: usize = 1000;
+
+#[derive(Debug, Clone)]
+pub(crate) struct MatchConfig {
+    pub(crate) rules: Rules,
+    pub(crate) roster: Vec<RosterEntry>,
+    pub(crate) seed: u64,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct RosterEntry {
+    pub(crate) strategy: String,
+    pub(crate) color: Option<TankColor>,
+    pub(crate) point: Option<BoardPoint>,
+    pub(crate) direction: Option<Direction>,
+}
+
+impl RosterEntry {
+    pub(crate) fn new(strategy: &str) -> Self {
+        Self {
+            strategy: strategy.to_string(),
+            color: None,
+            point: None,
+            direction: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct TankColor {
+    pub(crate) r: u8,
+    pub(crate) g: u8,
+    pub(crate) b: u8,
+}
+
+impl TankColor {
+    // Parse a "#RRGGBB" hex color.
+    pub(crate) fn parse(hex: &str) -> Option<Self> {
+        let digits = hex.strip_prefix('#')?;
+        if digits.len() != 6 || !digits.is_ascii() {
+            return None;
+        }
+        let channel = |index: usize| u8::from_str_radix(&digits[index..index + 2], 16).ok();
+        Some(Self {
+            r: channel(0)?,
+            g: channel(2)?,
+            b: channel(4)?,
+        })
+    }
+}
+
+impl Display for TankColor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{:02X}{:02X}{:02X}", self.r, self.g, self.b)
+    }
+}
+
+impl Default for MatchConfig {
+    fn default() -> Self {
+        Self {
+            rules: Default::default(),
+            roster: strategies::DEFAULT_ROSTER.iter().map(|id| RosterEntry::new(id)).collect(),
+            seed: rand::random(),
+        }
+    }
+}
+
+impl MatchConfig {
+    // Load a match file. Files ending in .json are read as JSON, everything else as TOML.
+    pub(crate) fn load(path: &Path) -> Result<Self, ConfigError> {
+        let content = fs::read_to_string(path)
+            .map_err(|error| ConfigError::Read(path.to_path_buf(), error.to_string()))?;
+        let is_json = path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+        let file: MatchFile = if is_json {
+            serde_json::from_str(&content).map_err(|error| ConfigError::Parse(path.to_path_buf(), error.to_string()))?
+        } else {
+            toml::from_str(&content).map_err(|error| ConfigError::Parse(path.to_path_buf(), error.to_string()))?
+        };
+        file.into_match_config()
+    }
+
+    pub(crate) fn validate(&self, registry: &Registry) -> Result<(), ConfigError> {
+        let dimension = &self.rules.dimension;
+        if dimension.width == 0 || dimension.width > MAX_BOARD_SIZE {
+            return Err(ConfigError::invalid("board.width", &format!("must be from 1 to {}", MAX_BOARD_SIZE)));
+        }
+        if dimension.height == 0 || dimension.height > MAX_BOARD_SIZE {
+            return Err(ConfigError::invalid("board.height", &format!("must be from 1 to {}", MAX_BOARD_SIZE)));
+        }
+        if self.rules.max_rounds == 0 {
+            return Err(ConfigError::invalid("rules.max_rounds", "must be at least 1"));
+        }
+        if self.rules.max_energy == 0 {
+            return Err(ConfigError::invalid("rules.max_energy", "must be at least 1"));
+        }
+        if self.rules.move_time_limit_ms == Some(0) {
+            return Err(ConfigError::invalid("rules.move_time_limit_ms", "must be at least 1"));
+        }
+        if self.rules.match_time_limit_ms == Some(0) {
+            return Err(ConfigError::invalid("rules.match_time_limit_ms", "must be at least 1"));
+        }
+        for (index, tie_break) in self.rules.tie_breaks.iter().enumerate() {
+            if self.rules.tie_breaks[..index].contains(tie_break) {
+                return Err(ConfigError::invalid(&format!("rules.tie_breaks[{}]", index),
+                                                &format!("{} is already listed", tie_break)));
+            }
+        }
+        if self.roster.is_empty() {
+            return Err(ConfigError::invalid("tanks", "at least one tank is required"));
+        }
+        let cells = dimension.width.saturating_mul(dimension.height);
+        if self.roster.len() > cells {
+            return Err(ConfigError::invalid("tanks", &format!("{} tanks do not fit on a board with {} cells",
+                                                               self.roster.len(), cells)));
+        }
+
+        for (index, entry) in self.roster.iter().enumerate() {
+            let Some(registered) = registry.lookup(&entry.strategy) else {
+                return Err(ConfigError::invalid(&format!("tanks[{}].strategy", index),
+                                                &format!("unknown strategy '{}'", entry.strategy)));
+            };
+            if registered.kind == StrategyKind::Remote {
+                let same_bot = |other: &RosterEntry| {
+                    registry.lookup(&other.strategy).is_some_and(|other| other.id == registered.id)
+                };
+                if let Some(other) = self.roster[..index].iter().position(same_bot) {
+                    return Err(ConfigError::invalid(&format!("tanks[{}].strategy", index),
+                                                    &format!("remote bot '{}' can only play one tank, it is tanks[{}]",
+                                                             registered.id, other)));
+                }
+            }
+            if let Some(point) = &entry.point {
+                if point.x < 0 || point.x >= dimension.width as isize {
+                    return Err(ConfigError::invalid(&format!("tanks[{}].position.x", index),
+                                                    &format!("{} is outside the board width {}", point.x, dimension.width)));
+                }
+                if point.y < 0 || point.y >= dimension.height as isize {
+                    return Err(ConfigError::invalid(&format!("tanks[{}].position.y", index),
+                                                    &format!("{} is outside the board height {}", point.y, dimension.height)));
+                }
+                let duplicate = self.roster[..index].iter().position(|other| other.point.as_ref() == Some(point));
+                if let Some(other) = duplicate {
+                    return Err(ConfigError::invalid(&format!("tanks[{}].position", index),
+                                                    &format!("same position as tanks[{}]", other)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn create_engine(&self, registry: &Registry) -> Result<Engine, ConfigError> {
+        self.validate(registry)?;
+        let setups = self.roster.iter()
+            .map(|entry| TankSetup {
+                strategy: registry.create(&entry.strategy).unwrap(),
+                point: entry.point.clone(),
+                direction: entry.direction,
+                color: entry.color.map(|color| color.to_string()),
+            })
+            .collect();
+        Ok(Engine::new(self.rules.clone(), setups, self.seed))
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct MatchFile {
+    seed: Option<u64>,
+    #[serde(default)]
+    board: BoardFile,
+    #[serde(default)]
+    rules: RulesFile,
+    #[serde(default)]
+    tanks: Vec<TankFile>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+struct BoardFile {
+    width: Option<usize>,
+    height: Option<usize>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+struct RulesFile {
+    max_rounds: Option<usize>,
+    max_energy: Option<usize>,
+    fire_range: Option<usize>,
+    tie_breaks: Option<Vec<TieBreak>>,
+    move_time_limit_ms: Option<u64>,
+    match_time_limit_ms: Option<u64>,
+    fault_policy: Option<FaultPolicy>,
+    vision_radius: Option<usize>,
+    line_of_sight: Option<bool>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct TankFile {
+    strategy: String,
+    color: Option<String>,
+    position: Option<BoardPoint>,
+    direction: Option<Direction>,
+}
+
+impl MatchFile {
+    fn into_match_config(self) -> Result<MatchConfig, ConfigError> {
+        let defaults = Rules::default();
+        let rules = Rules {
+            dimension: Dimension {
+                width: self.board.width.unwrap_or(defaults.dimension.width),
+                height: self.board.height.unwrap_or(defaults.dimension.height),
+            },
+            max_rounds: self.rules.max_rounds.unwrap_or(defaults.max_rounds),
+            max_energy: self.rules.max_energy.unwrap_or(defaults.max_energy),
+            fire_range: self.rules.fire_range.unwrap_or(defaults.fire_range),
+            tie_breaks: self.rules.tie_breaks.unwrap_or(defaults.tie_breaks),
+            move_time_limit_ms: self.rules.move_time_limit_ms.or(defaults.move_time_limit_ms),
+            match_time_limit_ms: self.rules.match_time_limit_ms.or(defaults.match_time_limit_ms),
+            fault_policy: self.rules.fault_policy.unwrap_or(defaults.fault_policy),
+            vision_radius: self.rules.vision_radius.or(defaults.vision_radius),
+            line_of_sight: self.rules.line_of_sight.unwrap_or(defaults.line_of_sight),
+        };
+
+        let mut config = MatchConfig {
+            rules,
+            seed: self.seed.unwrap_or_else(rand::random),
+            ..Default::default()
+        };
+        if !self.tanks.is_empty() {
+            config.roster = self.tanks.into_iter().enumerate()
+                .map(|(index, tank)| {
+                    let color = match tank.color {
+                        Some(color) => Some(TankColor::parse(&color).ok_or_else(|| ConfigError::invalid(
+                            &format!("tanks[{}].color", index), &format!("'{}' is not a #RRGGBB color", color)))?),
+                        None => None,
+                    };
+                    Ok(RosterEntry {
+                        strategy: tank.strategy,
+                        color,
+                        point: tank.position,
+                        direction: tank.direction,
+                    })
+                })
+                .collect::<Result<_, ConfigError>>()?;
+        }
+        Ok(config)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum ConfigError {
+    Read(PathBuf, String),
+    Parse(PathBuf, String),
+    Invalid { field: String, message: String },
+}
+
+impl ConfigError {
+    fn invalid(field: &str, message: &str) -> Self {
+        Self::Invalid { field: field.to_string(), message: message.to_string() }
+    }
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Read(path, error) => write!(f, "can't read {}: {}", path.display(), error),
+            Self::Parse(path, error) => write!(f, "can't parse {}: {}", path.display(), error),
+            Self::Invalid { field, message } => write!(f, "invalid match config, {}: {}", field, message),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
@@ -0,0 +1,206 @@
+use std::ffi::{
+    c_char,
+    c_void,
+    CStr
+};
+use std::fmt::{
+    Display,
+    Formatter
+};
+use std::fs;
+use std::path::Path;
+use std::sync::{
+    Arc,
+    Mutex
+};
+use libloading::Library;
+use crate::engine::{
+    Move,
+    NextMoveInput,
+    Strategy
+};
+use crate::registry::{
+    FailedStrategy,
+    Registry
+};
+
+
+pub(crate) const BOT_ABI_VERSION: u32 = 1;
+
+// Name of the function every bot library exports. It takes no arguments and returns a pointer to a static
+// BotDescriptor, see bots/example_bot.c for the C declarations.
+const ENTRY_POINT: &[u8] = b"robot_challenge_bot_v1";
+
+// Shared libraries run in the process without a sandbox, so only load bots you trust.
+#[repr(C)]
+pub(crate) struct BotDescriptor {
+    abi_version: u32,
+    // NUL terminated UTF-8, valid as long as the library is loaded.
+    name: *const c_char,
+    author: *const c_char,
+    // Returns null on failure.
+    create: Option<unsafe extern "C" fn() -> *mut c_void>,
+    destroy: Option<unsafe extern "C" fn(bot: *mut c_void)>,
+    // May be null.
+    seed: Option<unsafe extern "C" fn(bot: *mut c_void, seed: u64)>,
+    // The input is NextMoveInput as JSON, not NUL terminated. Returns 0 Fire, 1 TurnLeft, 2 Forward,
+    // 3 TurnRight or 4 Wait.
+    next_move: Option<unsafe extern "C" fn(bot: *mut c_void, input: *const c_char, len: usize) -> i32>,
+}
+
+struct BotLibrary {
+    // Keeps the descriptor and the functions loaded.
+    _library: Library,
+    descriptor: *const BotDescriptor,
+    name: String,
+    author: String,
+}
+
+// The descriptor is static data in the library and only read.
+unsafe impl Send for BotLibrary {}
+unsafe impl Sync for BotLibrary {}
+
+pub(crate) struct DylibStrategy {
+    library: Arc<BotLibrary>,
+    bot: *mut c_void,
+}
+
+// The ABI lets a bot instance move between threads, see the threading contract in bots/example_bot.c.
+unsafe impl Send for DylibStrategy {}
+
+impl BotLibrary {
+    fn load(path: &Path) -> Result<Self, DylibError> {
+        let error = |message: String| DylibError(format!("{}: {}", path.display(), message));
+        // Safety: loading runs the library's initializers, bots in the bots directory are trusted.
+        let library = unsafe { Library::new(path) }.map_err(|e| error(e.to_string()))?;
+        let descriptor = unsafe {
+            let entry_point = library.get::<unsafe extern "C" fn() -> *const BotDescriptor>(ENTRY_POINT)
+                .map_err(|e| error(e.to_string()))?;
+            entry_point()
+        };
+        let descriptor_ref = unsafe { descriptor.as_ref() }.ok_or_else(|| error("null bot descriptor".to_string()))?;
+        if descriptor_ref.abi_version != BOT_ABI_VERSION {
+            return Err(error(format!("unsupported bot ABI version {}", descriptor_ref.abi_version)));
+        }
+        if descriptor_ref.create.is_none() || descriptor_ref.destroy.is_none() || descriptor_ref.next_move.is_none() {
+            return Err(error("create, destroy and next_move are required".to_string()));
+        }
+        let string = |pointer: *const c_char, field: &str| -> Result<String, DylibError> {
+            if pointer.is_null() {
+                return Err(error(format!("null {}", field)));
+            }
+            Ok(unsafe { CStr::from_ptr(pointer) }.to_string_lossy().into_owned())
+        };
+        let name = string(descriptor_ref.name, "name")?;
+        let author = string(descriptor_ref.author, "author")?;
+        Ok(Self {
+            _library: library,
+            descriptor,
+            name,
+            author,
+        })
+    }
+
+    fn descriptor(&self) -> &BotDescriptor {
+        unsafe { &*self.descriptor }
+    }
+}
+
+impl DylibStrategy {
+    // A null bot means create failed, the other functions must not be called with it.
+    fn create(library: Arc<BotLibrary>) -> Result<Self, DylibError> {
+        let create = library.descriptor().create.unwrap();
+        let bot = unsafe { create() };
+        if bot.is_null() {
+            return Err(DylibError(format!("bot {}: create returned null", library.name)));
+        }
+        Ok(Self { library, bot })
+    }
+}
+
+impl Strategy for DylibStrategy {
+    fn name(&self) -> String {
+        self.library.name.clone()
+    }
+
+    fn author(&self) -> String {
+        self.library.author.clone()
+    }
+
+    fn next_move(&mut self, input: NextMoveInput) -> Move {
+        let json = serde_json::to_vec(&input).unwrap();
+        let next_move = self.library.descriptor().next_move.unwrap();
+        let code = unsafe { next_move(self.bot, json.as_ptr() as *const c_char, json.len()) };
+        match code {
+            0 => Move::Fire,
+            1 => Move::TurnLeft,
+            2 => Move::Forward,
+            3 => Move::TurnRight,
+            4 => Move::Wait,
+            _ => panic!("bot {} returned unknown move {}", self.library.name, code),
+        }
+    }
+
+    fn seed(&mut self, seed: u64) {
+        if let Some(function) = self.library.descriptor().seed {
+            unsafe { function(self.bot, seed) };
+        }
+    }
+}
+
+impl Drop for DylibStrategy {
+    fn drop(&mut self) {
+        let destroy = self.library.descriptor().destroy.unwrap();
+        unsafe { destroy(self.bot) };
+    }
+}
+
+// Register every shared library in the directory, with the file name without "lib" prefix and extension as id.
+// A missing directory has no bots. Libraries that fail to load or to create a bot are reported and skipped.
+// The bot created to check the library is the instance the registry reads name and author from.
+pub(crate) fn register_dir(registry: &mut Registry, dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut paths: Vec<_> = entries.filter_map(|entry| entry.ok()).map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| {
+            extension == "so" || extension == "dylib" || extension == "dll"
+        }))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let probe = match BotLibrary::load(&path).and_then(|library| DylibStrategy::create(Arc::new(library))) {
+            Ok(probe) => probe,
+            Err(error) => {
+                eprintln!("Skipping bot {}", error);
+                continue;
+            }
+        };
+        let library = probe.library.clone();
+        let probe = Mutex::new(Some(probe));
+        let stem = path.file_stem().unwrap().to_string_lossy();
+        let id = stem.strip_prefix("lib").unwrap_or(&stem).to_string();
+        let description = format!("Shared library: {}", path.display());
+        registry.register(&id, &description, move || -> Box<dyn Strategy> {
+            if let Some(probe) = probe.lock().unwrap().take() {
+                return Box::new(probe);
+            }
+            match DylibStrategy::create(library.clone()) {
+                Ok(strategy) => Box::new(strategy),
+                Err(error) => Box::new(FailedStrategy::new(&library.name, &library.author, &error.to_string())),
+            }
+        });
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct DylibError(String);
+
+impl Display for DylibError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for DylibError {}
@@ -0,0 +1,1145 @@
+use std::cmp::Reverse;
+use std::default::Default;
+use std::fmt::{
+    Debug,
+    Display,
+    Formatter
+};
+use std::str::FromStr;
+use std::time::Duration;
+use rand::distributions::{
+    Distribution,
+    Standard
+};
+use rand::{
+    Rng,
+    SeedableRng
+};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use serde::{
+    Deserialize,
+    Serialize
+};
+use crate::host::{
+    FaultKind,
+    StrategyHost
+};
+
+
+pub(crate) struct Engine {
+    seed: u64,
+    rng: StdRng,
+    round: usize,
+    // Number of moves played so far in the whole game.
+    moves: usize,
+    next_tank_indexes: Vec<usize>,
+    rules: Rules,
+    tanks: Vec<Tank>,
+    result: Option<MatchResult>,
+}
+
+#[derive(Debug)]
+pub(crate) enum Step {
+    NewRound(usize),
+    Turn(Box<Turn>),
+    GameOver(Option<usize>),
+}
+
+#[derive(Debug)]
+pub(crate) struct Turn {
+    pub(crate) index: usize,
+    // The input the strategy was given, None for dead tanks.
+    pub(crate) input: Option<NextMoveInput>,
+    pub(crate) next_move: Move,
+    pub(crate) laser: Option<Laser>,
+    pub(crate) hit: Option<Hit>,
+    pub(crate) fault: Option<Fault>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Hit {
+    pub(crate) index: usize,
+    // Energy of the hit tank after the hit.
+    pub(crate) energy: usize,
+    pub(crate) frag: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Rules {
+    pub(crate) dimension: Dimension,
+    pub(crate) max_rounds: usize,
+    pub(crate) max_energy: usize,
+    pub(crate) fire_range: usize,
+    // Compared in order between the surviving tanks when max_rounds is reached.
+    #[serde(default = "TieBreak::default_order")]
+    pub(crate) tie_breaks: Vec<TieBreak>,
+    #[serde(default)]
+    pub(crate) move_time_limit_ms: Option<u64>,
+    #[serde(default)]
+    pub(crate) match_time_limit_ms: Option<u64>,
+    // What happens to a tank whose strategy is too slow or panics.
+    #[serde(default)]
+    pub(crate) fault_policy: FaultPolicy,
+    // Strategies only see opponents within this many squares in any direction, see can_see.
+    #[serde(default)]
+    pub(crate) vision_radius: Option<usize>,
+    // Strategies only see opponents straight ahead, behind or to the sides that no other tank hides.
+    #[serde(default)]
+    pub(crate) line_of_sight: bool,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            dimension: Default::default(),
+            max_rounds: 100,
+            max_energy: 5,
+            fire_range: 5,
+            tie_breaks: TieBreak::default_order(),
+            move_time_limit_ms: None,
+            match_time_limit_ms: None,
+            fault_policy: Default::default(),
+            vision_radius: None,
+            line_of_sight: false,
+        }
+    }
+}
+
+impl Rules {
+    pub(crate) fn move_time_limit(&self) -> Option<Duration> {
+        self.move_time_limit_ms.map(Duration::from_millis)
+    }
+
+    pub(crate) fn match_time_limit(&self) -> Option<Duration> {
+        self.match_time_limit_ms.map(Duration::from_millis)
+    }
+
+    pub(crate) fn has_limited_vision(&self) -> bool {
+        self.vision_radius.is_some() || self.line_of_sight
+    }
+
+    // Whether a tank at from can see the square to. With line of sight the first tank in the way blocks the view,
+    // dead tanks included. A tank always sees its own square.
+    pub(crate) fn can_see(&self, from: &BoardPoint, to: &BoardPoint, tanks: &[BoardPoint]) -> bool {
+        let (dx, dy) = (to.x - from.x, to.y - from.y);
+        let distance = dx.abs().max(dy.abs());
+        if self.vision_radius.is_some_and(|radius| distance > radius as isize) {
+            return false;
+        }
+        if self.line_of_sight {
+            if dx != 0 && dy != 0 {
+                return false;
+            }
+            let (step_x, step_y) = (dx.signum(), dy.signum());
+            return (1..distance).all(|i| {
+                let between = BoardPoint { x: from.x + step_x * i, y: from.y + step_y * i };
+                !tanks.contains(&between)
+            });
+        }
+        true
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum FaultPolicy {
+    // The move is forfeited and the tank waits.
+    #[default]
+    Wait,
+    // The tank waits and loses one energy.
+    LoseEnergy,
+    // The tank loses all energy and its strategy is not asked again.
+    Disqualify,
+}
+
+impl Display for FaultPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::Wait => write!(f, "wait"),
+            Self::LoseEnergy => write!(f, "lose-energy"),
+            Self::Disqualify => write!(f, "disqualify"),
+        }
+    }
+}
+
+impl FromStr for FaultPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().replace('_', "-").as_str() {
+            "wait" => Ok(Self::Wait),
+            "lose-energy" => Ok(Self::LoseEnergy),
+            "disqualify" => Ok(Self::Disqualify),
+            _ => Err(format!("unknown fault policy '{}', expected wait, lose-energy or disqualify", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Fault {
+    #[serde(flatten)]
+    pub(crate) kind: FaultKind,
+    pub(crate) policy: FaultPolicy,
+    // Energy of the tank after the policy was applied.
+    pub(crate) energy: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum TieBreak {
+    Energy,
+    Frags,
+    Hits,
+}
+
+impl TieBreak {
+    pub(crate) fn default_order() -> Vec<Self> {
+        vec![Self::Energy, Self::Frags, Self::Hits]
+    }
+
+    fn score(&self, tank: &Tank) -> usize {
+        match *self {
+            Self::Energy => tank.energy,
+            Self::Frags => tank.frags,
+            Self::Hits => tank.hits,
+        }
+    }
+}
+
+impl Display for TieBreak {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::Energy => write!(f, "energy"),
+            Self::Frags => write!(f, "frags"),
+            Self::Hits => write!(f, "hits"),
+        }
+    }
+}
+
+impl FromStr for TieBreak {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "energy" => Ok(Self::Energy),
+            "frags" => Ok(Self::Frags),
+            "hits" => Ok(Self::Hits),
+            _ => Err(format!("unknown tie-break '{}', expected energy, frags or hits", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct MatchResult {
+    pub(crate) round: usize,
+    pub(crate) winner: Option<usize>,
+    // The tie-break that separated the winner from the runner-up when the game ran out of rounds.
+    #[serde(default)]
+    pub(crate) decided_by: Option<TieBreak>,
+    // Every tank, best first. Tanks that can't be separated share a place.
+    #[serde(default)]
+    pub(crate) ranking: Vec<Placement>,
+}
+
+impl MatchResult {
+    pub(crate) fn summary<F: Fn(usize) -> String>(&self, tank_name: F) -> String {
+        match (self.winner, self.decided_by) {
+            (Some(index), None) => format!("The winner is {} after {} rounds", tank_name(index), self.round),
+            (Some(index), Some(tie_break)) => format!("The winner is {} on {} after {} rounds", tank_name(index),
+                                                      tie_break, self.round),
+            (None, _) => format!("Draw after {} rounds", self.round),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Placement {
+    pub(crate) tank: usize,
+    pub(crate) place: usize,
+    // None for tanks that survived.
+    #[serde(default)]
+    pub(crate) eliminated: Option<Elimination>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Elimination {
+    pub(crate) round: usize,
+    // Counted from 1 over the whole game.
+    pub(crate) move_number: usize,
+    // The tank that fragged it, None if it was lost by a fault.
+    pub(crate) by: Option<usize>,
+}
+
+pub(crate) struct TankSetup {
+    pub(crate) strategy: Box<dyn Strategy>,
+    pub(crate) point: Option<BoardPoint>,
+    pub(crate) direction: Option<Direction>,
+    // "#RRGGBB", passed on to the strategies.
+    pub(crate) color: Option<String>,
+}
+
+impl Engine {
+    // Fixed start points must be unique and on the board, tanks without one are placed randomly.
+    pub(crate) fn new(rules: Rules, setups: Vec<TankSetup>, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let fixed_points: Vec<BoardPoint> = setups.iter().filter_map(|setup| setup.point.clone()).collect();
+        let mut points = BoardPoint::get_unique_random_vec(setups.len() - fixed_points.len(), &rules.dimension,
+                                                           &fixed_points, &mut rng).into_iter();
+
+        let mut tanks: Vec<Tank> = setups.into_iter()
+            .map(|setup| {
+                let point = setup.point.unwrap_or_else(|| points.next().unwrap());
+                let mut tank = Tank::new(setup.strategy, point, rules.max_energy);
+                // Every strategy gets its own seed derived from the match seed.
+                tank.strategy.seed(rng.gen());
+                tank.direction = setup.direction.unwrap_or_default();
+                tank.color = setup.color;
+                tank
+            })
+            .collect();
+        let names: Vec<String> = tanks.iter().map(|tank| tank.strategy.name()).collect();
+        for (id, tank) in tanks.iter_mut().enumerate() {
+            let info = GameInfo { id, names: names.clone(), rules: rules.clone() };
+            tank.strategy.notify(Event::GameStart(info), rules.move_time_limit());
+        }
+
+        Self {
+            seed,
+            rng,
+            round: 0,
+            moves: 0,
+            next_tank_indexes: vec![],
+            rules,
+            tanks,
+            result: None,
+        }
+    }
+
+    pub(crate) fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub(crate) fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    pub(crate) fn round(&self) -> usize {
+        self.round
+    }
+
+    pub(crate) fn winner(&self) -> Option<usize> {
+        self.result.as_ref().and_then(|result| result.winner)
+    }
+
+    // Set once the game is over.
+    pub(crate) fn result(&self) -> Option<&MatchResult> {
+        self.result.as_ref()
+    }
+
+    pub(crate) fn tanks(&self) -> &[Tank] {
+        &self.tanks
+    }
+
+    // Advance the game by one event: the start of a new round, a single tank move or the end of the game.
+    pub(crate) fn step(&mut self) -> Step {
+        if self.result.is_some() {
+            return Step::GameOver(self.winner());
+        }
+        match self.next_tank_indexes.pop() {
+            Some(index) => Step::Turn(Box::new(self.play_turn(index))),
+            None => self.new_round(),
+        }
+    }
+
+    pub(crate) fn run_to_completion(&mut self) -> Option<usize> {
+        loop {
+            if let Step::GameOver(winner) = self.step() {
+                return winner;
+            }
+        }
+    }
+
+    fn new_round(&mut self) -> Step {
+        if self.round > 0 {
+            self.notify_alive(Event::RoundEnd(self.round));
+        }
+        self.round += 1;
+        // max_rounds rounds have been played.
+        if self.round > self.rules.max_rounds {
+            return self.finish();
+        }
+        self.next_tank_indexes = self.gen_new_round_indexes();
+        if self.next_tank_indexes.len() > 1 {
+            self.notify_alive(Event::RoundStart(self.round));
+            Step::NewRound(self.round)
+        } else {
+            self.next_tank_indexes.clear();
+            self.finish()
+        }
+    }
+
+    fn finish(&mut self) -> Step {
+        let result = self.adjudicate();
+        let winner = result.winner;
+        for tank in self.tanks.iter_mut() {
+            tank.strategy.notify(Event::GameEnd(result.clone()), self.rules.move_time_limit());
+        }
+        self.result = Some(result);
+        Step::GameOver(winner)
+    }
+
+    // Rank the survivors first by the tie-breaks in order, then the other tanks by how late they were fragged.
+    // A lone survivor always wins, otherwise the first tie-break that separates the two best tanks decides,
+    // or the game is a draw.
+    fn adjudicate(&self) -> MatchResult {
+        let key = |index: usize| -> Vec<usize> {
+            let tank = &self.tanks[index];
+            match tank.eliminated {
+                Some(elimination) if !tank.is_alive() => vec![0, elimination.move_number],
+                _ => {
+                    let scores = self.rules.tie_breaks.iter().map(|tie_break| tie_break.score(tank));
+                    std::iter::once(tank.is_alive() as usize).chain(scores).collect()
+                }
+            }
+        };
+        let mut order: Vec<usize> = (0..self.tanks.len()).collect();
+        order.sort_by_key(|&index| Reverse(key(index)));
+
+        let mut ranking: Vec<Placement> = Vec::new();
+        for (position, &index) in order.iter().enumerate() {
+            let place = match ranking.last() {
+                Some(previous) if key(previous.tank) == key(index) => previous.place,
+                _ => position + 1,
+            };
+            ranking.push(Placement { tank: index, place, eliminated: self.tanks[index].eliminated });
+        }
+
+        let best = key(order[0]);
+        let (winner, decided_by) = match order.get(1).map(|&index| key(index)) {
+            _ if best[0] == 0 => (None, None),
+            None => (Some(order[0]), None),
+            Some(runner_up) => match best.iter().zip(&runner_up).position(|(best, other)| best != other) {
+                Some(0) => (Some(order[0]), None),
+                Some(position) => (Some(order[0]), Some(self.rules.tie_breaks[position - 1])),
+                None => (None, None),
+            },
+        };
+        MatchResult {
+            // The game ends when the next round would start, so the last round played is the one before.
+            round: self.round - 1,
+            winner,
+            decided_by,
+            ranking,
+        }
+    }
+
+    fn play_turn(&mut self, index: usize) -> Turn {
+        self.moves += 1;
+        let (next_move, input, fault) = self.get_next_move(index);
+        let mut laser = None;
+        let mut hit = None;
+        match next_move {
+            Move::TurnLeft => {
+                self.move_turn_left(index);
+            }
+            Move::TurnRight => {
+                self.move_turn_right(index);
+            }
+            Move::Forward => {
+                self.move_forward(index);
+            }
+            Move::Fire => {
+                let (fire_laser, fire_hit) = self.move_fire(index);
+                laser = Some(fire_laser);
+                hit = fire_hit;
+            }
+            Move::Wait => {}
+        }
+        // Tanks fragged earlier in the round keep the last move they made.
+        if input.is_some() {
+            self.tanks[index].last_move = Some(LastMove {
+                round: self.round,
+                next_move: next_move.clone(),
+                hit: hit.as_ref().map(|hit| hit.index),
+            });
+        }
+        Turn {
+            index,
+            input,
+            next_move,
+            laser,
+            hit,
+            fault,
+        }
+    }
+
+    fn is_valid_point(&self, point: &BoardPoint) -> bool {
+        0 <= point.x && point.x < self.rules.dimension.width as isize
+            && 0 <= point.y && point.y < self.rules.dimension.height as isize
+    }
+
+    fn is_tank(&self, point: &BoardPoint) -> bool {
+        self.tanks.iter().any(|tank| tank.point == *point)
+    }
+
+    fn get_tank_index(&self, point: &BoardPoint) -> usize {
+        self.tanks.iter().position(|tank| tank.point == *point).unwrap()
+    }
+
+    fn next_move_input(&self, current_index: usize) -> NextMoveInput {
+        let mut next_move_input = NextMoveInput {
+            version: INPUT_VERSION,
+            game_board: self.rules.dimension.clone(),
+            fire_range: self.rules.fire_range,
+            round: self.round,
+            max_rounds: self.rules.max_rounds,
+            ..Default::default()
+        };
+        let own_point = &self.tanks[current_index].point;
+        let points: Vec<BoardPoint> = self.tanks.iter().map(|tank| tank.point.clone()).collect();
+        for (index, tank) in self.tanks.iter().enumerate() {
+            if index != current_index && !self.rules.can_see(own_point, &tank.point, &points) {
+                continue;
+            }
+            let tank_status = TankStatus {
+                direction: tank.direction,
+                location: tank.point.clone(),
+                is_alive: tank.is_alive(),
+                id: index,
+                name: tank.strategy.name(),
+                color: tank.color.clone(),
+                energy: tank.energy,
+                hits: tank.hits,
+                frags: tank.frags,
+                last_move: tank.last_move.clone(),
+            };
+            if index == current_index {
+                next_move_input.own_status = tank_status
+            } else {
+                next_move_input.opponent_status.push(tank_status);
+            }
+        }
+        next_move_input
+    }
+
+    fn notify_alive(&mut self, event: Event) {
+        for tank in self.tanks.iter_mut().filter(|tank| tank.is_alive()) {
+            tank.strategy.notify(event.clone(), self.rules.move_time_limit());
+        }
+    }
+
+    fn gen_new_round_indexes(&mut self) -> Vec<usize> {
+        // Randomize next tank index
+        let mut indexes: Vec<usize> = (0..self.tanks.len()).collect();
+        indexes.shuffle(&mut self.rng);
+
+        indexes.into_iter().filter(|&index| {
+            let tank = self.tanks.get(index).unwrap();
+            // Only tanks with energy can move.
+            tank.is_alive()
+        }).collect()
+    }
+
+    fn get_next_move(&mut self, index: usize) -> (Move, Option<NextMoveInput>, Option<Fault>) {
+        let tank = self.tanks.get(index).unwrap();
+        if !tank.is_alive() {
+            return (Move::Wait, None, None);
+        }
+        let next_move_input = self.next_move_input(index);
+        let move_limit = self.rules.move_time_limit();
+        let match_limit = self.rules.match_time_limit();
+        let tank = self.tanks.get_mut(index).unwrap();
+        match tank.strategy.next_move(next_move_input.clone(), move_limit, match_limit) {
+            Ok(next_move) => (next_move, Some(next_move_input), None),
+            Err(kind) => (Move::Wait, Some(next_move_input), Some(self.apply_fault_policy(index, kind))),
+        }
+    }
+
+    fn apply_fault_policy(&mut self, index: usize, kind: FaultKind) -> Fault {
+        let policy = self.rules.fault_policy;
+        let tank = self.tanks.get_mut(index).unwrap();
+        match policy {
+            FaultPolicy::Wait => {}
+            FaultPolicy::LoseEnergy => tank.energy -= 1,
+            FaultPolicy::Disqualify => {
+                tank.energy = 0;
+                tank.strategy.disqualify();
+            }
+        }
+        if !tank.is_alive() {
+            tank.eliminated = Some(Elimination {
+                round: self.round,
+                move_number: self.moves,
+                by: None,
+            });
+        }
+        Fault {
+            kind,
+            policy,
+            energy: tank.energy,
+        }
+    }
+
+    fn move_turn_left(&mut self, index: usize) {
+        let tank = self.tanks.get_mut(index).unwrap();
+        tank.direction = tank.direction.counter_clockwise();
+    }
+
+    fn move_turn_right(&mut self, index: usize) {
+        let tank = self.tanks.get_mut(index).unwrap();
+        tank.direction = tank.direction.clockwise();
+    }
+
+    fn move_forward(&mut self, index: usize) {
+        let tank = self.tanks.get(index).unwrap();
+        let new_point = tank.point.with_offset(tank.direction, 1);
+        let is_valid_point = self.is_valid_point(&new_point);
+        let is_tank = self.is_tank(&new_point);
+        if is_valid_point && !is_tank {
+            let tank = self.tanks.get_mut(index).unwrap();
+            tank.point = new_point;
+        }
+    }
+
+    fn move_fire(&mut self, index: usize) -> (Laser, Option<Hit>) {
+        let tank = self.tanks.get(index).unwrap();
+        let mut laser = Laser {
+            point: tank.point.clone(),
+            direction: tank.direction,
+            length: self.rules.fire_range,
+            hit: None,
+        };
+        let mut hit = None;
+        // Change laser length if there is a tank or board edge.
+        for i in 1..=self.rules.fire_range {
+            let fire_point = laser.point.with_offset(laser.direction, i as isize);
+            if !self.is_valid_point(&fire_point) {
+                laser.length = i - 1;
+                break;
+            } else if self.is_tank(&fire_point) {
+                laser.length = i - 1;
+                laser.hit = Some(fire_point.clone());
+                // Update tank energy, hits, frags.
+                let hit_index = self.get_tank_index(&fire_point);
+                let hit_tank = self.tanks.get_mut(hit_index).unwrap();
+                let mut frag = false;
+                if hit_tank.energy > 0 {
+                    hit_tank.energy -= 1;
+                    if hit_tank.energy == 0 {
+                        frag = true;
+                        hit_tank.eliminated = Some(Elimination {
+                            round: self.round,
+                            move_number: self.moves,
+                            by: Some(index),
+                        });
+                    }
+                    let hit_energy = hit_tank.energy;
+                    hit_tank.strategy.notify(Event::Hit(index), self.rules.move_time_limit());
+                    let tank = self.tanks.get_mut(index).unwrap();
+                    tank.hits += 1;
+                    tank.frags += if frag { 1 } else { 0 };
+                    if frag {
+                        tank.strategy.notify(Event::Frag(hit_index), self.rules.move_time_limit());
+                    }
+                    hit = Some(Hit {
+                        index: hit_index,
+                        energy: hit_energy,
+                        frag,
+                    });
+                }
+                break;
+            }
+        }
+        (laser, hit)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Laser {
+    pub(crate) point: BoardPoint,
+    pub(crate) direction: Direction,
+    pub(crate) length: usize,
+    pub(crate) hit: Option<BoardPoint>,
+}
+
+pub(crate) struct Tank {
+    pub(crate) strategy: StrategyHost,
+    pub(crate) energy: usize,
+    pub(crate) hits: usize,
+    pub(crate) frags: usize,
+    pub(crate) eliminated: Option<Elimination>,
+    pub(crate) last_move: Option<LastMove>,
+    pub(crate) color: Option<String>,
+    pub(crate) point: BoardPoint,
+    // Set to random available Point when adding to Board.
+    pub(crate) direction: Direction, // Set to random direction when adding to Board.
+}
+
+impl Debug for Tank {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Tank(strategy: {}, energy: {}, hits: {}, frags: {:?}, {:?}, direction: {:?}",
+               self.strategy.name(), self.energy, self.hits, self.frags, self.point, self.direction)
+    }
+}
+
+impl Tank {
+    fn new(strategy: Box<dyn Strategy>, point: BoardPoint, energy: usize) -> Self {
+        Self {
+            strategy: StrategyHost::new(strategy),
+            energy,
+            hits: 0,
+            frags: 0,
+            eliminated: None,
+            last_move: None,
+            color: None,
+            point,
+            direction: Default::default(),
+        }
+    }
+
+    pub(crate) fn is_alive(&self) -> bool {
+        self.energy > 0
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub(crate) enum Move {
+    Fire,
+    TurnLeft,
+    Forward,
+    TurnRight,
+    Wait,
+}
+
+impl Distribution<Move> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Move {
+        match rng.gen_range(0..=4) {
+            0 => Move::Fire,
+            1 => Move::TurnLeft,
+            2 => Move::Forward,
+            3 => Move::TurnRight,
+            _ => Move::Wait,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Dimension {
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+}
+
+impl Default for Dimension {
+    fn default() -> Self {
+        Self { width: 20, height: 20 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub(crate) struct BoardPoint {
+    pub(crate) x: isize,
+    pub(crate) y: isize,
+}
+
+impl BoardPoint {
+    pub(crate) fn with_offset(&self, direction: Direction, offset: isize) -> Self {
+        Self {
+            x: self.x + direction.x() * offset,
+            y: self.y + direction.y() * offset
+        }
+    }
+
+    fn random<R: Rng>(dimension: &Dimension, rng: &mut R) -> Self {
+        Self {
+            x: rng.gen_range(0..dimension.width as isize),
+            y: rng.gen_range(0..dimension.height as isize),
+        }
+    }
+
+    fn get_unique_random_vec<R: Rng>(len: usize, dimension: &Dimension, taken: &[Self], rng: &mut R) -> Vec<Self> {
+        let mut points: Vec<Self> = Vec::new();
+        while points.len() < len {
+            let point = Self::random(dimension, rng);
+            if !points.contains(&point) && !taken.contains(&point) {
+                points.push(point);
+            }
+        }
+        points
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub(crate) enum Direction {
+    #[default]
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Direction {
+    pub(crate) fn degrees(&self) -> f32 {
+        match *self {
+            Self::North => 0.0,
+            Self::East => 90.0,
+            Self::South => 180.0,
+            Self::West => 270.0,
+        }
+    }
+
+    fn x(&self) -> isize {
+        match *self {
+            Self::North => 0,
+            Self::East => 1,
+            Self::South => 0,
+            Self::West => -1,
+        }
+    }
+
+    fn y(&self) -> isize {
+        match *self {
+            Self::North => -1,
+            Self::East => 0,
+            Self::South => 1,
+            Self::West => 0,
+        }
+    }
+
+    pub(crate) fn clockwise(&self) -> Direction {
+        match *self {
+            Self::North => Self::East,
+            Self::East => Self::South,
+            Self::South => Self::West,
+            Self::West => Self::North,
+        }
+    }
+
+    pub(crate) fn counter_clockwise(&self) -> Direction {
+        match *self {
+            Self::North => Self::West,
+            Self::East => Self::North,
+            Self::South => Self::East,
+            Self::West => Self::South,
+        }
+    }
+}
+
+// Version of NextMoveInput. Fields added after version 1 have defaults, so older inputs in replays still load.
+//   1  game_board, own_status, opponent_status and fire_range, tanks with direction, location and is_alive
+//   2  version, round and max_rounds, tanks with id, name, energy, hits, frags and last_move
+//   3  tanks with color
+pub(crate) const INPUT_VERSION: u32 = 3;
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct TankStatus {
+    pub(crate) direction: Direction,
+    pub(crate) location: BoardPoint,
+    pub(crate) is_alive: bool,
+    // Index of the tank in the game, the same for the whole game. Opponents are listed in id order and dead
+    // tanks stay in the list unless the vision rules hide them, so the id can be used to follow an opponent
+    // from move to move.
+    #[serde(default)]
+    pub(crate) id: usize,
+    // Name of the tank's strategy.
+    #[serde(default)]
+    pub(crate) name: String,
+    // "#RRGGBB" if the match gave the tank a color.
+    #[serde(default)]
+    pub(crate) color: Option<String>,
+    #[serde(default)]
+    pub(crate) energy: usize,
+    #[serde(default)]
+    pub(crate) hits: usize,
+    #[serde(default)]
+    pub(crate) frags: usize,
+    // None before the tank's first move.
+    #[serde(default)]
+    pub(crate) last_move: Option<LastMove>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct LastMove {
+    pub(crate) round: usize,
+    #[serde(rename = "move")]
+    pub(crate) next_move: Move,
+    // Id of the tank hit by a Fire.
+    pub(crate) hit: Option<usize>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub(crate) struct NextMoveInput {
+    #[serde(default = "NextMoveInput::first_version")]
+    pub(crate) version: u32,
+    pub(crate) game_board: Dimension,
+    pub(crate) own_status: TankStatus,
+    pub(crate) opponent_status: Vec<TankStatus>,
+    pub(crate) fire_range: usize,
+    #[serde(default)]
+    pub(crate) round: usize,
+    #[serde(default)]
+    pub(crate) max_rounds: usize,
+}
+
+impl NextMoveInput {
+    fn first_version() -> u32 {
+        1
+    }
+}
+
+// Stops a strategy from outside its thread, see Strategy::abort_handle.
+pub(crate) type AbortHandle = Box<dyn FnOnce() + Send>;
+
+// Strategies run on their own thread, see StrategyHost.
+pub(crate) trait Strategy: Send {
+    fn name(&self) -> String;
+    fn author(&self) -> String;
+    fn next_move(&mut self, input: NextMoveInput) -> Move;
+
+    // Called once before the game starts with a seed derived from the match seed.
+    // Strategies that use randomness should seed their own generator from it.
+    fn seed(&mut self, _seed: u64) {}
+
+    // Events during the game, called in order between the moves. Each gets the move time limit, see
+    // StrategyHost::notify.
+    fn on_game_start(&mut self, _info: &GameInfo) {}
+    // Only tanks that are still alive are told about rounds.
+    fn on_round_start(&mut self, _round: usize) {}
+    fn on_round_end(&mut self, _round: usize) {}
+    // Another tank hit this one, also when it was the last hit.
+    fn on_hit(&mut self, _by: usize) {}
+    // This tank fragged another one.
+    fn on_frag(&mut self, _tank: usize) {}
+    fn on_game_end(&mut self, _result: &MatchResult) {}
+
+    // A way to stop the strategy while it is stuck in a call, like killing its bot process. The host takes it
+    // before the strategy moves to its thread and uses it when it gives up on the strategy.
+    fn abort_handle(&self) -> Option<AbortHandle> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct GameInfo {
+    // Id of the tank the strategy plays, as in TankStatus.
+    pub(crate) id: usize,
+    // Strategy names by tank id.
+    pub(crate) names: Vec<String>,
+    pub(crate) rules: Rules,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Event {
+    GameStart(GameInfo),
+    RoundStart(usize),
+    RoundEnd(usize),
+    Hit(usize),
+    Frag(usize),
+    GameEnd(MatchResult),
+}
+
+impl Event {
+    pub(crate) fn deliver(&self, strategy: &mut dyn Strategy) {
+        match self {
+            Self::GameStart(info) => strategy.on_game_start(info),
+            Self::RoundStart(round) => strategy.on_round_start(*round),
+            Self::RoundEnd(round) => strategy.on_round_end(*round),
+            Self::Hit(by) => strategy.on_hit(*by),
+            Self::Frag(tank) => strategy.on_frag(*tank),
+            Self::GameEnd(result) => strategy.on_game_end(result),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::{
+        Random,
+        Slacker
+    };
+
+    fn engine(tanks: usize, tie_breaks: Vec<TieBreak>) -> Engine {
+        let rules = Rules { tie_breaks, ..Rules::default() };
+        let setups = (0..tanks)
+            .map(|_| TankSetup { strategy: Box::new(Slacker::default()), point: None, direction: None, color: None })
+            .collect();
+        let mut engine = Engine::new(rules, setups, 1);
+        // As if the game ends when the second round would start.
+        engine.round = 2;
+        engine
+    }
+
+    fn set_scores(engine: &mut Engine, index: usize, energy: usize, frags: usize, hits: usize) {
+        let tank = &mut engine.tanks[index];
+        tank.energy = energy;
+        tank.frags = frags;
+        tank.hits = hits;
+    }
+
+    fn eliminate(engine: &mut Engine, index: usize, move_number: usize) {
+        let tank = &mut engine.tanks[index];
+        tank.energy = 0;
+        tank.eliminated = Some(Elimination { round: 1, move_number, by: None });
+    }
+
+    // (tank, place) best first.
+    fn places(result: &MatchResult) -> Vec<(usize, usize)> {
+        result.ranking.iter().map(|placement| (placement.tank, placement.place)).collect()
+    }
+
+    #[test]
+    fn lone_survivor_wins_and_the_others_rank_by_elimination() {
+        let mut engine = engine(3, TieBreak::default_order());
+        eliminate(&mut engine, 0, 7);
+        eliminate(&mut engine, 2, 3);
+        let result = engine.adjudicate();
+        assert_eq!(result.round, 1);
+        assert_eq!(result.winner, Some(1));
+        assert_eq!(result.decided_by, None);
+        assert_eq!(places(&result), vec![(1, 1), (0, 2), (2, 3)]);
+    }
+
+    #[test]
+    fn first_separating_tie_break_decides() {
+        let mut engine = engine(2, TieBreak::default_order());
+        set_scores(&mut engine, 0, 3, 1, 2);
+        set_scores(&mut engine, 1, 3, 2, 0);
+        let result = engine.adjudicate();
+        assert_eq!(result.winner, Some(1));
+        assert_eq!(result.decided_by, Some(TieBreak::Frags));
+        assert_eq!(places(&result), vec![(1, 1), (0, 2)]);
+    }
+
+    #[test]
+    fn tie_breaks_apply_in_the_configured_order() {
+        let mut engine = engine(2, vec![TieBreak::Hits, TieBreak::Energy]);
+        set_scores(&mut engine, 0, 5, 0, 1);
+        set_scores(&mut engine, 1, 2, 0, 3);
+        let result = engine.adjudicate();
+        assert_eq!(result.winner, Some(1));
+        assert_eq!(result.decided_by, Some(TieBreak::Hits));
+    }
+
+    #[test]
+    fn equal_survivors_share_a_place_and_draw() {
+        let mut engine = engine(4, TieBreak::default_order());
+        set_scores(&mut engine, 0, 2, 1, 1);
+        set_scores(&mut engine, 1, 4, 0, 0);
+        set_scores(&mut engine, 2, 4, 0, 0);
+        eliminate(&mut engine, 3, 2);
+        let result = engine.adjudicate();
+        assert_eq!(result.winner, None);
+        assert_eq!(result.decided_by, None);
+        assert_eq!(places(&result), vec![(1, 1), (2, 1), (0, 3), (3, 4)]);
+    }
+
+    #[test]
+    fn game_without_survivors_is_a_draw() {
+        let mut engine = engine(2, TieBreak::default_order());
+        eliminate(&mut engine, 0, 4);
+        eliminate(&mut engine, 1, 5);
+        let result = engine.adjudicate();
+        assert_eq!(result.winner, None);
+        assert_eq!(result.decided_by, None);
+        assert_eq!(places(&result), vec![(1, 1), (0, 2)]);
+    }
+
+    fn point(x: isize, y: isize) -> BoardPoint {
+        BoardPoint { x, y }
+    }
+
+    fn vision(vision_radius: Option<usize>, line_of_sight: bool) -> Rules {
+        Rules { vision_radius, line_of_sight, ..Rules::default() }
+    }
+
+    // Tanks at fixed points, ids in order.
+    fn engine_at(rules: Rules, points: &[(isize, isize)]) -> Engine {
+        let setups = points.iter()
+            .map(|&(x, y)| TankSetup {
+                strategy: Box::new(Slacker::default()),
+                point: Some(point(x, y)),
+                direction: Some(Direction::North),
+                color: None,
+            })
+            .collect();
+        Engine::new(rules, setups, 1)
+    }
+
+    fn visible_ids(input: &NextMoveInput) -> Vec<usize> {
+        input.opponent_status.iter().map(|status| status.id).collect()
+    }
+
+    #[test]
+    fn whole_board_is_visible_without_limits() {
+        let rules = Rules::default();
+        assert!(!rules.has_limited_vision());
+        assert!(rules.can_see(&point(0, 0), &point(19, 7), &[point(5, 2)]));
+    }
+
+    #[test]
+    fn vision_radius_uses_chebyshev_distance() {
+        let rules = vision(Some(2), false);
+        assert!(rules.can_see(&point(5, 5), &point(7, 7), &[]));
+        assert!(rules.can_see(&point(5, 5), &point(3, 6), &[]));
+        assert!(!rules.can_see(&point(5, 5), &point(8, 5), &[]));
+        assert!(!rules.can_see(&point(5, 5), &point(6, 2), &[]));
+    }
+
+    #[test]
+    fn line_of_sight_is_straight_and_blocked_by_the_first_tank() {
+        let rules = vision(None, true);
+        let tanks = [point(0, 0), point(0, 3), point(0, 6)];
+        assert!(rules.can_see(&point(0, 0), &point(0, 3), &tanks));
+        assert!(!rules.can_see(&point(0, 0), &point(0, 6), &tanks));
+        assert!(rules.can_see(&point(0, 0), &point(9, 0), &tanks));
+        assert!(!rules.can_see(&point(0, 0), &point(1, 1), &tanks));
+        assert!(!rules.can_see(&point(0, 0), &point(2, 5), &tanks));
+    }
+
+    #[test]
+    fn vision_radius_and_line_of_sight_combine() {
+        let rules = vision(Some(3), true);
+        assert!(rules.can_see(&point(0, 0), &point(3, 0), &[]));
+        assert!(!rules.can_see(&point(0, 0), &point(4, 0), &[]));
+        assert!(!rules.can_see(&point(0, 0), &point(2, 2), &[]));
+    }
+
+    #[test]
+    fn input_only_shows_visible_opponents_and_always_the_own_tank() {
+        let mut engine = engine_at(vision(Some(4), true), &[(0, 0), (0, 2), (0, 4), (3, 0), (1, 1)]);
+        // Dead tanks still block the view.
+        engine.tanks[1].energy = 0;
+        let input = engine.next_move_input(0);
+        assert_eq!(input.own_status.id, 0);
+        assert_eq!(visible_ids(&input), vec![1, 3]);
+
+        let input = engine_at(vision(Some(0), false), &[(0, 0), (0, 1)]).next_move_input(1);
+        assert_eq!(input.own_status.id, 1);
+        assert_eq!(input.own_status.location, point(0, 1));
+        assert!(input.opponent_status.is_empty());
+    }
+
+    // Point, direction, energy, hits, frags and elimination of a tank.
+    type TankState = (BoardPoint, Direction, usize, usize, usize, Option<Elimination>);
+
+    // The result and every tank's final state.
+    fn play_random(seed: u64) -> (String, Vec<TankState>) {
+        let setups = (0..4)
+            .map(|_| TankSetup { strategy: Box::new(Random::default()), point: None, direction: None, color: None })
+            .collect();
+        let mut engine = Engine::new(Rules::default(), setups, seed);
+        engine.run_to_completion();
+        let result = serde_json::to_string(engine.result().unwrap()).unwrap();
+        let tanks = engine.tanks().iter()
+            .map(|tank| (tank.point.clone(), tank.direction, tank.energy, tank.hits, tank.frags, tank.eliminated))
+            .collect();
+        (result, tanks)
+    }
+
+    #[test]
+    fn same_seed_plays_the_same_game() {
+        assert_eq!(play_random(42), play_random(42));
+        assert_ne!(play_random(42), play_random(43));
+    }
+}
@@ -6,15 +6,23 @@ use iced::{
     Color,
     Command,
     Element,
+    event,
+    Event,
     executor,
+    keyboard,
     Length,
     mouse,
     Point,
     Rectangle,
     Renderer,
+    Size,
+    subscription,
+    Subscription,
     Theme,
+    time,
     Vector
 };
+use iced::keyboard::KeyCode;
 use iced::widget::canvas::{
     Cache,
     Geometry,
@@ -25,6 +33,7 @@ use iced::widget::canvas::{
     stroke
 };
 use iced::widget::{
+    button,
     canvas,
     Canvas,
     Column,
@@ -32,172 +41,521 @@ use iced::widget::{
     row,
     Row,
     scrollable,
+    slider,
     text
 };
-use std::default::Default;
-use std::fmt::{
-    Debug,
-    Formatter
-};
-use std::io;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
 use std::time::Duration;
 use iced::widget::canvas::path::lyon_path::geom::Angle;
 use iced::widget::canvas::path::lyon_path::geom::euclid::Transform2D;
-use rand::distributions::{
-    Distribution,
-    Standard
+use crate::config::{
+    MatchConfig,
+    TankColor
+};
+use crate::engine::{
+    BoardPoint,
+    Dimension,
+    Direction,
+    Elimination,
+    Engine,
+    Laser,
+    Rules,
+    Step
 };
-use rand;
-use rand::Rng;
-use rand::seq::SliceRandom;
-use crate::strategies::{
-    Dummy,
-    FireFire,
-    Random,
-    Slacker,
-    Spinner
+use crate::registry::Registry;
+use crate::replay::{
+    Recorder,
+    Replay
 };
 
 
+pub(crate) struct GuiFlags {
+    pub(crate) source: GameSource,
+    pub(crate) step_delay: Duration,
+}
+
+pub(crate) enum GameSource {
+    Live {
+        config: MatchConfig,
+        registry: Registry,
+        record: Option<PathBuf>,
+    },
+    Replay(Replay),
+}
+
 pub(crate) struct RobotChallenge {
-    round: usize,
-    next_tank_indexs: Vec<usize>,
+    mode: Mode,
+    colors: Vec<Color>,
+    base_delay: Duration,
+    speed: usize,
     board_cache: Cache,
-    dimension: Dimension,
-    tanks: Vec<Tank>,
-    laser: Laser,
-    hit: Hit,
+    laser: Option<Laser>,
+    hit: Option<BoardPoint>,
+    // Tank whose vision is shaded on the board when the rules limit vision.
+    selected: Option<usize>,
 }
 
-impl Default for RobotChallenge {
-    fn default() -> Self {
-        Self {
-            round: 0,
-            next_tank_indexs: vec![],
-            board_cache: Default::default(),
-            dimension: Default::default(),
-            tanks: vec![],
-            laser: Default::default(),
-            hit: Default::default(),
+enum Mode {
+    Live(Box<LiveGame>),
+    Replay(Box<ReplayPlayer>),
+}
+
+struct LiveGame {
+    engine: Engine,
+    recorder: Option<Recorder<BufWriter<File>>>,
+    is_paused: bool,
+    phase: Phase,
+    // Kept to start new games from the end-of-game summary.
+    config: MatchConfig,
+    registry: Registry,
+    record: Option<PathBuf>,
+}
+
+// What the next animation tick of a live game does.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    NewGame,
+    Step,
+    Laser,
+    Hit,
+    GameOver,
+}
+
+impl LiveGame {
+    fn new(config: MatchConfig, registry: Registry, record: Option<PathBuf>) -> Self {
+        let engine = config.create_engine(&registry).expect("match config is validated before the GUI starts");
+        let mut live = Self {
+            engine,
+            recorder: None,
+            is_paused: false,
+            phase: Phase::NewGame,
+            config,
+            registry,
+            record,
+        };
+        live.start_recording();
+        println!("Seed {}", live.engine.seed());
+        live
+    }
+
+    // Start over with the same roster and rules. A recording is rewritten for the new game.
+    fn restart(&mut self, seed: u64) {
+        self.config.seed = seed;
+        self.engine = self.config.create_engine(&self.registry).expect("match config is validated before the GUI starts");
+        self.is_paused = false;
+        self.phase = Phase::NewGame;
+        self.start_recording();
+        println!("Seed {}", self.engine.seed());
+    }
+
+    fn start_recording(&mut self) {
+        // Drop the old recorder first so its file is flushed before it is created again.
+        self.recorder = None;
+        self.recorder = self.record.as_ref().and_then(|path| {
+            Recorder::create(path, &self.config, &self.engine)
+                .map_err(|error| eprintln!("Can't record to {}: {}", path.display(), error))
+                .ok()
+        });
+    }
+
+    fn step(&mut self) -> Step {
+        let step = self.engine.step();
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(error) = recorder.record(&step, &self.engine) {
+                eprintln!("Recording stopped: {}", error);
+                self.recorder = None;
+            }
         }
+        step
     }
 }
 
+struct ReplayPlayer {
+    replay: Replay,
+    frame: usize,
+    is_playing: bool,
+}
+
+impl ReplayPlayer {
+    fn last_frame(&self) -> usize {
+        self.replay.frames.len() - 1
+    }
+}
+
+// What the board and score table show for a tank, in live or replay mode.
+struct TankView {
+    name: String,
+    point: BoardPoint,
+    direction: Direction,
+    energy: usize,
+    hits: usize,
+    frags: usize,
+}
+
 impl RobotChallenge {
-    fn is_valid_point(&self, point: &BoardPoint) -> bool {
-        0 <= point.x && point.x < self.dimension.width as isize
-            && 0 <= point.y && point.y < self.dimension.height as isize
-    }
-
-    fn is_tank(&self, point: &BoardPoint) -> bool {
-        self.tanks.iter().any(|tank| tank.point == *point)
-    }
-
-    fn get_tank_mut(&mut self, point: &BoardPoint) -> &mut Tank {
-        self.tanks.iter_mut().find(|tank| tank.point == *point).unwrap()
-    }
-
-    fn next_move_input(&self, current_index: usize) -> NextMoveInput {
-        let mut next_move_input = NextMoveInput::default();
-        next_move_input.game_board = self.dimension.clone();
-        next_move_input.fire_range = Tank::FIRE_RANGE;
-        for index in 0..self.tanks.len() {
-            let tank = self.tanks.get(index).unwrap();
-            let tank_status = TankStatus {
-                direction: tank.direction.clone(),
-                location: tank.point.clone(),
-                is_alive: tank.energy > 0,
-            };
-            if index == current_index {
-                next_move_input.own_status = tank_status
-            } else {
-                next_move_input.opponent_status.push(tank_status);
+    const CELL_SIZE: f32 = 20.0;
+
+    // Speed factors selectable with the speed slider, the last one plays without any delay.
+    const SPEEDS: [(&'static str, f32); 8] = [
+        ("0.1x", 0.1), ("0.25x", 0.25), ("0.5x", 0.5), ("1x", 1.0), ("2x", 2.0), ("5x", 5.0), ("10x", 10.0),
+        ("Instant", f32::INFINITY)
+    ];
+    const NORMAL_SPEED: usize = 3;
+
+    // The tick interval can't be zero, so instant speed ticks as fast as the timer allows.
+    const MIN_STEP_DELAY: Duration = Duration::from_millis(1);
+
+    fn step_delay(&self) -> Duration {
+        self.base_delay.div_f32(Self::SPEEDS[self.speed].1).max(Self::MIN_STEP_DELAY)
+    }
+
+    fn is_animating(&self) -> bool {
+        match &self.mode {
+            Mode::Live(live) => !live.is_paused && live.phase != Phase::GameOver,
+            Mode::Replay(player) => player.is_playing,
+        }
+    }
+
+    fn change_speed(&mut self, speed: usize) {
+        self.speed = speed.min(Self::SPEEDS.len() - 1);
+    }
+
+    fn speed_controls(&self) -> Element<'_, Message> {
+        let speed = slider(0..=(Self::SPEEDS.len() - 1) as u8, self.speed as u8, |speed| Message::SpeedChanged(speed as usize))
+            .width(200);
+        row![
+            text("Speed").size(15),
+            speed,
+            text(Self::SPEEDS[self.speed].0).size(15),
+        ]
+            .spacing(10)
+            .into()
+    }
+
+    fn live_controls(live: &LiveGame) -> Element<'_, Message> {
+        row![
+            button(if live.is_paused { "Resume" } else { "Pause" }).on_press(Message::TogglePlay),
+            button("Step").on_press(Message::StepForward),
+            text("Space: pause, Right: step, Up/Down: speed").size(15),
+        ]
+            .spacing(5)
+            .into()
+    }
+
+    fn summary(live: &LiveGame) -> Element<'_, Message> {
+        let tanks = live.engine.tanks();
+        let placements = live.engine.result().into_iter()
+            .flat_map(|result| &result.ranking)
+            .map(|placement| {
+                let mut line = format!("{}. {}", placement.place, tanks[placement.tank].strategy.name());
+                match placement.eliminated {
+                    Some(Elimination { by: Some(by), round, .. }) => {
+                        line.push_str(&format!(", fragged by {} in round {}", tanks[by].strategy.name(), round));
+                    }
+                    Some(Elimination { by: None, round, .. }) => {
+                        line.push_str(&format!(", lost by a fault in round {}", round));
+                    }
+                    None => {}
+                }
+                Element::from(text(line).size(15))
+            });
+        let mut elements = vec![Element::from(text(Self::result_summary(&live.engine)).size(20))];
+        elements.extend(placements);
+        elements.push(Element::from(row![
+            button("New game").on_press(Message::NewGame),
+            button("Rematch with same seed").on_press(Message::Rematch),
+            text("N: new game, R: rematch").size(15),
+        ]
+            .spacing(5)));
+        Column::with_children(elements)
+            .spacing(5)
+            .into()
+    }
+
+    fn game_over_text(&self) -> Option<String> {
+        match &self.mode {
+            Mode::Live(live) if live.phase == Phase::GameOver => Some(match live.engine.winner() {
+                Some(index) => format!("{} wins", live.engine.tanks()[index].strategy.name()),
+                None => "Draw".to_string(),
+            }),
+            _ => None,
+        }
+    }
+
+    fn rules(&self) -> &Rules {
+        match &self.mode {
+            Mode::Live(live) => live.engine.rules(),
+            Mode::Replay(player) => &player.replay.header.rules,
+        }
+    }
+
+    fn dimension(&self) -> &Dimension {
+        &self.rules().dimension
+    }
+
+    fn vision_controls(&self) -> Element<'_, Message> {
+        let status = match self.selected {
+            Some(index) => format!("Shading what {} can't see. 1-9: select tank, 0: show all",
+                                   self.tank_views()[index].name),
+            None => "1-9: shade what a tank can't see".to_string(),
+        };
+        text(status).size(15).into()
+    }
+
+    fn tank_views(&self) -> Vec<TankView> {
+        match &self.mode {
+            Mode::Live(live) => live.engine.tanks().iter()
+                .map(|tank| TankView {
+                    name: tank.strategy.name(),
+                    point: tank.point.clone(),
+                    direction: tank.direction,
+                    energy: tank.energy,
+                    hits: tank.hits,
+                    frags: tank.frags,
+                })
+                .collect(),
+            Mode::Replay(player) => player.replay.header.tanks.iter().zip(&player.replay.frames[player.frame].tanks)
+                .map(|(record, state)| TankView {
+                    name: record.name.clone(),
+                    point: state.point.clone(),
+                    direction: state.direction,
+                    energy: state.energy,
+                    hits: state.hits,
+                    frags: state.frags,
+                })
+                .collect(),
+        }
+    }
+
+    fn update_live(&mut self, message: Message) {
+        let Mode::Live(live) = &mut self.mode else {
+            return;
+        };
+        match message {
+            Message::Tick if !live.is_paused => self.animate_live(),
+            Message::TogglePlay => {
+                live.is_paused = !live.is_paused;
             }
+            Message::StepForward if live.is_paused && live.phase != Phase::GameOver => self.step_live_move(),
+            // Only from the end-of-game summary, so a stray key doesn't throw a running game away.
+            Message::NewGame if live.phase == Phase::GameOver => self.restart_live(rand::random()),
+            Message::Rematch if live.phase == Phase::GameOver => {
+                let seed = live.engine.seed();
+                self.restart_live(seed);
+            }
+            _ => {}
         }
-        next_move_input
-    }
-
-    fn gen_new_round_indexes(&self) -> Vec<usize> {
-        // Randomize next tank index
-        let mut indexes: Vec<usize> = (0..self.tanks.len()).collect();
-        let mut rng = rand::thread_rng();
-        indexes.shuffle(&mut rng);
-
-        indexes.into_iter().filter(|&index| {
-            let tank = self.tanks.get(index).unwrap();
-            // Only tanks with energy can move.
-            tank.energy > 0
-        }).collect()
-    }
-
-    fn get_next_move(&mut self, index: usize) -> Move {
-        let mut next_move = Move::Wait;
-        let tank = self.tanks.get(index).unwrap();
-        if tank.is_alive() {
-            let next_move_input = self.next_move_input(index);
-            println!("{:?}", tank);
-            let tank = self.tanks.get_mut(index).unwrap();
-            next_move = tank.strategy.next_move(next_move_input);
-            println!("{:?}", next_move);
+    }
+
+    fn restart_live(&mut self, seed: u64) {
+        if let Mode::Live(live) = &mut self.mode {
+            live.restart(seed);
+            self.laser = None;
+            self.hit = None;
+            self.board_cache.clear();  // Trigger draw on canvas.
         }
-        next_move
     }
 
-    fn move_turn_left(&mut self, index: usize) {
-        let tank = self.tanks.get_mut(index).unwrap();
-        tank.direction = tank.direction.counter_clockwise();
+    // Play the engine until the next tank move and show it at once, used to step while paused.
+    fn step_live_move(&mut self) {
+        let Mode::Live(live) = &mut self.mode else {
+            return;
+        };
+        self.laser = None;
+        self.hit = None;
+        live.phase = Phase::Step;
+        loop {
+            match live.step() {
+                Step::NewRound(round) => {
+                    println!("NewRound {}", round);
+                }
+                Step::Turn(turn) => {
+                    println!("{:?}", live.engine.tanks()[turn.index]);
+                    println!("{:?}", turn.next_move);
+                    if let Some(fault) = &turn.fault {
+                        println!("{:?}", fault);
+                    }
+                    self.hit = turn.laser.as_ref().and_then(|laser| laser.hit.clone());
+                    // Resuming finishes the laser animation before the next move.
+                    if turn.laser.is_some() {
+                        live.phase = Phase::Laser;
+                    }
+                    self.laser = turn.laser;
+                    break;
+                }
+                Step::GameOver(_) => {
+                    Self::print_winner(&live.engine);
+                    live.phase = Phase::GameOver;
+                    break;
+                }
+            }
+        }
+        self.board_cache.clear();  // Trigger draw on canvas.
     }
 
-    fn move_turn_right(&mut self, index: usize) {
-        let tank = self.tanks.get_mut(index).unwrap();
-        tank.direction = tank.direction.clockwise();
+    fn print_winner(engine: &Engine) {
+        println!("EndGame");
+        println!("{}", Self::result_summary(engine));
     }
 
-    fn move_forward(&mut self, index: usize) {
-        let tank = self.tanks.get(index).unwrap();
-        let new_point = tank.point.with_offset(tank.direction, 1);
-        let is_valid_point = self.is_valid_point(&new_point);
-        let is_tank = self.is_tank(&new_point);
-        if is_valid_point && !is_tank {
-            let tank = self.tanks.get_mut(index).unwrap();
-            tank.point = new_point;
+    fn result_summary(engine: &Engine) -> String {
+        match engine.result() {
+            Some(result) => result.summary(|index| {
+                let tank = &engine.tanks()[index];
+                format!("{} by {}", tank.strategy.name(), tank.strategy.author())
+            }),
+            None => String::new(),
         }
     }
 
-    fn move_fire(&mut self, index: usize) {
-        let tank = self.tanks.get(index).unwrap();
-        self.laser.point = tank.point.clone();
-        self.laser.direction = tank.direction.clone();
-        self.laser.length = Tank::FIRE_RANGE;
-        self.laser.is_visible = true;
-        // Change laser length if there is a tank or board edge.
-        let fire_direction = self.laser.direction.clone();
-        for i in 1..=Tank::FIRE_RANGE {
-            let fire_point = self.laser.point.with_offset(fire_direction, i as isize);
-            if !self.is_valid_point(&fire_point) {
-                self.laser.length = i - 1;
-                break;
-            } else if self.is_tank(&fire_point) {
-                self.laser.hit = true;
-                self.laser.length = i - 1;
-                self.hit.point = fire_point.clone();
-                // Update tank energy, hits, frags.
-                let hit_tank = self.get_tank_mut(&fire_point);
-                let mut frag = false;
-                if hit_tank.energy > 0 {
-                    hit_tank.energy -= 1;
-                    if hit_tank.energy == 0 {
-                        frag = true;
+    fn animate_live(&mut self) {
+        let Mode::Live(live) = &mut self.mode else {
+            return;
+        };
+        live.phase = match live.phase {
+            Phase::NewGame => {
+                println!("NewGame");
+                Phase::Step
+            }
+            Phase::Step => {
+                match live.step() {
+                    Step::NewRound(round) => {
+                        println!("NewRound {}", round);
+                        Phase::Step
                     }
-                    let tank = self.tanks.get_mut(index).unwrap();
-                    tank.hits += 1;
-                    tank.frags += if frag { 1 } else { 0 };
+                    Step::Turn(turn) => {
+                        println!("{:?}", live.engine.tanks()[turn.index]);
+                        println!("{:?}", turn.next_move);
+                        if let Some(fault) = &turn.fault {
+                            println!("{:?}", fault);
+                        }
+                        self.board_cache.clear();  // Trigger draw on canvas.
+                        if turn.laser.is_some() {
+                            self.laser = turn.laser;
+                            Phase::Laser
+                        } else {
+                            Phase::Step
+                        }
+                    }
+                    Step::GameOver(_) => {
+                        Self::print_winner(&live.engine);
+                        Phase::GameOver
+                    }
+                }
+            }
+            Phase::Laser => {
+                println!("Laser");
+                // Reset laser
+                let laser = self.laser.take();
+                self.board_cache.clear();  // Trigger draw on canvas.
+                // Perform hit if needed.
+                match laser.and_then(|laser| laser.hit) {
+                    Some(hit) => {
+                        self.hit = Some(hit);
+                        Phase::Hit
+                    }
+                    None => Phase::Step
+                }
+            }
+            Phase::Hit => {
+                println!("Hit");
+                // Reset hit
+                self.hit = None;
+                self.board_cache.clear();  // Trigger draw on canvas.
+                Phase::Step
+            }
+            Phase::GameOver => Phase::GameOver,
+        };
+    }
+
+    fn update_replay(&mut self, message: Message) {
+        let Mode::Replay(player) = &mut self.mode else {
+            return;
+        };
+        match message {
+            Message::TogglePlay => {
+                player.is_playing = !player.is_playing;
+                // Start over when play is pressed at the end.
+                if player.is_playing && player.frame == player.last_frame() {
+                    player.frame = 0;
+                }
+            }
+            Message::Tick => {
+                if !player.is_playing {
+                    return;
+                }
+                player.frame = (player.frame + 1).min(player.last_frame());
+                if player.frame == player.last_frame() {
+                    player.is_playing = false;
                 }
-                break;
             }
+            Message::StepForward => {
+                player.is_playing = false;
+                player.frame = (player.frame + 1).min(player.last_frame());
+            }
+            Message::StepBackward => {
+                player.is_playing = false;
+                player.frame = player.frame.saturating_sub(1);
+            }
+            Message::RoundForward => {
+                player.is_playing = false;
+                player.frame = player.replay.next_round_frame(player.frame);
+            }
+            Message::RoundBackward => {
+                player.is_playing = false;
+                player.frame = player.replay.previous_round_frame(player.frame);
+            }
+            Message::Seek(frame) => {
+                player.is_playing = false;
+                player.frame = (frame as usize).min(player.last_frame());
+            }
+            _ => {}
+        }
+        self.show_replay_frame();
+    }
+
+    fn show_replay_frame(&mut self) {
+        if let Mode::Replay(player) = &self.mode {
+            self.laser = player.replay.frames[player.frame].laser.clone();
+            self.hit = self.laser.as_ref().and_then(|laser| laser.hit.clone());
+            self.board_cache.clear();  // Trigger draw on canvas.
         }
     }
 
+    fn replay_controls(player: &ReplayPlayer) -> Element<'_, Message> {
+        let frame = &player.replay.frames[player.frame];
+        let mut status = format!("Round {}, move {} of {}", frame.round, player.frame, player.last_frame());
+        if player.frame == player.last_frame() {
+            if let Some(result) = &player.replay.result {
+                status.push_str(". ");
+                status.push_str(&result.summary(|index| player.replay.header.tanks[index].name.clone()));
+            }
+        }
+
+        let buttons = row![
+            button("<< Round").on_press(Message::RoundBackward),
+            button("< Move").on_press(Message::StepBackward),
+            button(if player.is_playing { "Pause" } else { "Play" }).on_press(Message::TogglePlay),
+            button("Move >").on_press(Message::StepForward),
+            button("Round >>").on_press(Message::RoundForward),
+        ]
+            .spacing(5);
+
+        let timeline = slider(0..=player.last_frame() as u32, player.frame as u32, Message::Seek)
+            .width(400);
+
+        Column::with_children(vec![
+            Element::from(buttons),
+            Element::from(timeline),
+            Element::from(text(status).size(15)),
+            Element::from(text("Space: play, Left/Right: move, PageDown/PageUp: round, Up/Down: speed").size(15)),
+        ])
+            .spacing(5)
+            .into()
+    }
+
     fn score_row<'a, Message, Renderer>(name: String, name_color: Color, energy: String, hits: String, frags: String)
                                         -> Row<'a, Message, Renderer>
         where
@@ -233,151 +591,153 @@ impl RobotChallenge {
     }
 }
 
-impl RobotChallenge {
-    const MAX_ROUNDS: usize = 100;
-}
-
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) enum Message {
-    NewGame(Result<String, io::Error>),
-    NewRound(Result<String, io::Error>),
-    Move(Result<String, io::Error>),
-    Laser(Result<String, io::Error>),
-    Hit(Result<String, io::Error>),
-    EndGame(Result<String, io::Error>)
+    Tick,
+    NewGame,
+    Rematch,
+    TogglePlay,
+    StepForward,
+    StepBackward,
+    RoundForward,
+    RoundBackward,
+    Seek(u32),
+    SpeedChanged(usize),
+    SpeedUp,
+    SpeedDown,
+    // Select the tank with this index to shade its vision, or clear the selection.
+    SelectTank(Option<usize>),
 }
 
 impl Application for RobotChallenge {
     type Executor = executor::Default;
     type Message = Message;
     type Theme = Theme;
-    type Flags = ();
-
-    fn new(_flags: ()) -> (Self, Command<Message>) {
-        let mut strategies: Vec<Box<dyn Strategy>> = vec![
-            Box::new(Dummy::default()),
-            Box::new(Random::default()),
-            Box::new(Slacker::default()),
-            Box::new(Spinner::default()),
-            Box::new(FireFire::default()),
-            Box::new(Random::default()),
-            Box::new(Random::default())
-        ];
-        let dimension = Dimension::default();
-        let mut points = BoardPoint::get_unique_random_vec(strategies.len(), &dimension);
-        let mut colors = GameColors::get_tank_colors();
-
-        let mut tanks = Vec::new();
-        for _ in 0..strategies.len() {
-            let tank = Tank {
-                strategy: strategies.pop().unwrap(),
-                color: colors.pop().unwrap(),
-                point: points.pop().unwrap(),
-                ..Default::default()
-            };
-            tanks.push(tank);
-        }
+    type Flags = GuiFlags;
+
+    fn new(flags: GuiFlags) -> (Self, Command<Message>) {
+        let (mode, colors) = match flags.source {
+            GameSource::Live { config, registry, record } => {
+                let colors = config.roster.iter().enumerate()
+                    .map(|(index, entry)| GameColors::get_tank_color(index, entry.color))
+                    .collect();
+                let live = LiveGame::new(config, registry, record);
+                (Mode::Live(Box::new(live)), colors)
+            }
+            GameSource::Replay(replay) => {
+                println!("Seed {}", replay.header.seed);
+                let colors = replay.header.tanks.iter().enumerate()
+                    .map(|(index, tank)| {
+                        let color = tank.color.as_deref().and_then(TankColor::parse);
+                        GameColors::get_tank_color(index, color)
+                    })
+                    .collect();
+                let player = ReplayPlayer {
+                    replay,
+                    frame: 0,
+                    is_playing: false,
+                };
+                (Mode::Replay(Box::new(player)), colors)
+            }
+        };
 
-        (
-            Self {
-                tanks,
-                dimension,
-                ..Default::default()
-            },
-            Command::perform(Sleeper::sleep(Duration::from_millis(100)), Message::NewGame)
-        )
+        let robot_challenge = Self {
+            mode,
+            colors,
+            base_delay: flags.step_delay,
+            speed: Self::NORMAL_SPEED,
+            board_cache: Default::default(),
+            laser: None,
+            hit: None,
+            selected: None,
+        };
+        (robot_challenge, Command::none())
     }
 
     fn title(&self) -> String {
-        "Robot Challenge".to_string()
+        match self.mode {
+            Mode::Live(_) => "Robot Challenge".to_string(),
+            Mode::Replay(_) => "Robot Challenge - Replay".to_string(),
+        }
     }
 
     fn update(&mut self, message: Self::Message) -> Command<Self::Message> {
         match message {
-            Message::NewGame(_) => {
-                println!("NewGame");
-                Command::perform(Sleeper::sleep(Duration::from_millis(100)), Message::NewRound)
-            }
-            Message::NewRound(_) => {
-                self.round += 1;
-                println!("NewRound {}", self.round);
-                if self.round >= RobotChallenge::MAX_ROUNDS {
-                    Command::none()
-                } else {
-                    self.next_tank_indexs = self.gen_new_round_indexes();
-
-                    if self.next_tank_indexs.len() > 1 {
-                        Command::perform(Sleeper::sleep(Duration::from_millis(100)), Message::Move)
-                    } else {
-                        Command::perform(Sleeper::sleep(Duration::from_millis(100)), Message::EndGame)
-                    }
-                }
+            Message::SpeedChanged(speed) => {
+                self.change_speed(speed);
+                Command::none()
             }
-            Message::Move(_) => {
-                if self.next_tank_indexs.is_empty() {
-                    Command::perform(Sleeper::sleep(Duration::from_millis(100)), Message::NewRound)
-                } else {
-                    let index = self.next_tank_indexs.pop().unwrap();
-                    let next_move = self.get_next_move(index);
-                    match next_move {
-                        Move::TurnLeft => {
-                            self.move_turn_left(index);
-                        }
-                        Move::TurnRight => {
-                            self.move_turn_right(index);
-                        }
-                        Move::Forward => {
-                            self.move_forward(index);
-                        }
-                        Move::Fire => {
-                            self.move_fire(index);
-                        }
-                        Move::Wait => {}
-                    }
-                    self.board_cache.clear();  // Trigger draw on canvas.
-                    if Move::Fire == next_move {
-                        Command::perform(Sleeper::sleep(Duration::from_millis(100)), Message::Laser)
-                    } else {
-                        Command::perform(Sleeper::sleep(Duration::from_millis(100)), Message::Move)
-                    }
-                }
+            Message::SpeedUp => {
+                self.change_speed(self.speed + 1);
+                Command::none()
             }
-            Message::Laser(_) => {
-                println!("Laser");
-                let hit = self.laser.hit;
-                // Reset laser
-                self.laser = Default::default();
-                self.board_cache.clear();  // Trigger draw on canvas.
-                // Perform hit if needed.
-                if hit {
-                    self.hit.is_visible = true;
-                    self.board_cache.clear();  // Trigger draw on canvas.
-                    Command::perform(Sleeper::sleep(Duration::from_millis(100)), Message::Hit)
-                } else {
-                    Command::perform(Sleeper::sleep(Duration::from_millis(100)), Message::Move)
-                }
+            Message::SpeedDown => {
+                self.change_speed(self.speed.saturating_sub(1));
+                Command::none()
             }
-            Message::Hit(_) => {
-                println!("Hit");
-                // Reset hit
-                self.hit = Default::default();
+            Message::SelectTank(selected) => {
+                // Selecting the selected tank again clears the selection.
+                let tank_count = self.colors.len();
+                self.selected = selected.filter(|&index| index < tank_count && self.selected != Some(index));
                 self.board_cache.clear();  // Trigger draw on canvas.
-                Command::perform(Sleeper::sleep(Duration::from_millis(100)), Message::Move)
+                Command::none()
             }
-            Message::EndGame(_) => {
-                println!("EndGame");
-                let index = self.next_tank_indexs.pop().unwrap();
-                let tank = self.tanks.get_mut(index).unwrap();
-                println!("The winner is {} by {}", tank.strategy.name(), tank.strategy.author());
+            message => {
+                match self.mode {
+                    Mode::Live(_) => self.update_live(message),
+                    Mode::Replay(_) => self.update_replay(message),
+                }
                 Command::none()
             }
         }
     }
 
-    fn view(&self) -> Element<Message> {
+    fn subscription(&self) -> Subscription<Message> {
+        let keys = subscription::events_with(|event, status| {
+            if status == event::Status::Captured {
+                return None;
+            }
+            match event {
+                Event::Keyboard(keyboard::Event::KeyPressed { key_code, .. }) => match key_code {
+                    KeyCode::Space => Some(Message::TogglePlay),
+                    KeyCode::Right => Some(Message::StepForward),
+                    KeyCode::Left => Some(Message::StepBackward),
+                    KeyCode::PageUp => Some(Message::RoundForward),
+                    KeyCode::PageDown => Some(Message::RoundBackward),
+                    KeyCode::Up | KeyCode::Plus | KeyCode::Equals => Some(Message::SpeedUp),
+                    KeyCode::Down | KeyCode::Minus => Some(Message::SpeedDown),
+                    KeyCode::N => Some(Message::NewGame),
+                    KeyCode::R => Some(Message::Rematch),
+                    KeyCode::Key0 | KeyCode::Escape => Some(Message::SelectTank(None)),
+                    KeyCode::Key1 => Some(Message::SelectTank(Some(0))),
+                    KeyCode::Key2 => Some(Message::SelectTank(Some(1))),
+                    KeyCode::Key3 => Some(Message::SelectTank(Some(2))),
+                    KeyCode::Key4 => Some(Message::SelectTank(Some(3))),
+                    KeyCode::Key5 => Some(Message::SelectTank(Some(4))),
+                    KeyCode::Key6 => Some(Message::SelectTank(Some(5))),
+                    KeyCode::Key7 => Some(Message::SelectTank(Some(6))),
+                    KeyCode::Key8 => Some(Message::SelectTank(Some(7))),
+                    KeyCode::Key9 => Some(Message::SelectTank(Some(8))),
+                    _ => None,
+                },
+                _ => None,
+            }
+        });
+
+        // Changing speed or pausing replaces the tick subscription right away.
+        let ticks = if self.is_animating() {
+            time::every(self.step_delay()).map(|_| Message::Tick)
+        } else {
+            Subscription::none()
+        };
+        Subscription::batch([keys, ticks])
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        let dimension = self.dimension();
         let game_board: Canvas<&RobotChallenge, Message> = canvas(self as &Self)
-            .width(400)
-            .height(400);
+            .width(dimension.width as f32 * Self::CELL_SIZE)
+            .height(dimension.height as f32 * Self::CELL_SIZE);
 
         let score_row_headers = RobotChallenge::score_row(
             "Name".to_string(),
@@ -386,18 +746,27 @@ impl Application for RobotChallenge {
             "Hits".to_string(),
             "Frags".to_string());
 
-        let score_rows = self.tanks.iter().map(|tank|
+        let score_rows = self.tank_views().into_iter().zip(&self.colors).map(|(tank, color)|
             RobotChallenge::score_row::<Message, Renderer>(
-                tank.strategy.name().clone(),
-                tank.color.clone(),
-                tank.energy.to_string().clone(),
-                tank.hits.to_string().clone(),
-                tank.frags.to_string().clone()
+                tank.name,
+                *color,
+                tank.energy.to_string(),
+                tank.hits.to_string(),
+                tank.frags.to_string()
             )
         ).collect::<Vec<_>>();
 
         let mut elements: Vec<Element<Message>> = vec!();
         elements.push(Element::from(game_board));
+        match &self.mode {
+            Mode::Live(live) if live.phase == Phase::GameOver => elements.push(Self::summary(live)),
+            Mode::Live(live) => elements.push(Self::live_controls(live)),
+            Mode::Replay(player) => elements.push(Self::replay_controls(player)),
+        }
+        elements.push(self.speed_controls());
+        if self.rules().has_limited_vision() {
+            elements.push(self.vision_controls());
+        }
         elements.push(Element::from(score_row_headers));
         for row in score_rows {
             elements.push(Element::from(row));
@@ -416,15 +785,6 @@ impl Application for RobotChallenge {
     }
 }
 
-struct Sleeper;
-
-impl Sleeper {
-    async fn sleep(duration: Duration) -> Result<String, io::Error> {
-        std::thread::sleep(duration);
-        Ok("Booing".to_string())
-    }
-}
-
 impl<Message> canvas::Program<Message, Renderer> for RobotChallenge {
     type State = ();
 
@@ -442,12 +802,29 @@ impl<Message> canvas::Program<Message, Renderer> for RobotChallenge {
             let background = Path::rectangle(Point::new(0.0, 0.0), frame.size());
             frame.fill(&background, GameColors::LIGHT_GRAY);
 
+            // Shade the squares the selected tank can't see.
+            let tanks = self.tank_views();
+            if let Some(selected) = self.selected.filter(|_| self.rules().has_limited_vision()) {
+                let points: Vec<BoardPoint> = tanks.iter().map(|tank| tank.point.clone()).collect();
+                let dimension = self.dimension();
+                for y in 0..dimension.height as isize {
+                    for x in 0..dimension.width as isize {
+                        let point = BoardPoint { x, y };
+                        if !self.rules().can_see(&points[selected], &point, &points) {
+                            let cell = Path::rectangle(Point::new(x as f32 * 20.0, y as f32 * 20.0),
+                                                       Size::new(20.0, 20.0));
+                            frame.fill(&cell, GameColors::SHADE);
+                        }
+                    }
+                }
+            }
+
             // Draw tanks.
             let tank_path = tank_path();
             let center_transform = Transform2D::translation(-10.0, -10.0);
             let restore_transform = Transform2D::translation(10.0, 10.0);
 
-            for tank in &self.tanks {
+            for (tank, color) in tanks.iter().zip(&self.colors) {
                 frame.with_save(|frame| {
                     // Rotate tank.
                     let center_path = tank_path.transform(&center_transform);
@@ -457,7 +834,7 @@ impl<Message> canvas::Program<Message, Renderer> for RobotChallenge {
                     let x = (tank.point.x * 20) as f32;
                     let y = (tank.point.y * 20) as f32;
                     frame.translate(Vector::new(x, y));
-                    frame.fill(&restore_path, tank.color);
+                    frame.fill(&restore_path, *color);
 
                     // Draw dead tank. A X over a tank if dead.
                     if tank.energy == 0 {
@@ -480,14 +857,14 @@ impl<Message> canvas::Program<Message, Renderer> for RobotChallenge {
             }
 
             // Draw laser. A line from the shooting tank.
-            if self.laser.is_visible {
-                let laser_start = self.laser.point.with_offset(self.laser.direction, 1);
-                let laser_end = self.laser.point.with_offset(self.laser.direction, self.laser.length as isize);
+            if let Some(laser) = &self.laser {
+                let laser_start = laser.point.with_offset(laser.direction, 1);
+                let laser_end = laser.point.with_offset(laser.direction, laser.length as isize);
                 let mut start_point = Point::new(laser_start.x as f32 * 20.0 + 10.0, laser_start.y as f32 * 20.0 + 10.0);
                 let mut end_point = Point::new(laser_end.x as f32 * 20.0 + 10.0, laser_end.y as f32 * 20.0 + 10.0);
 
                 // Adjust laser end points from the default center of board cell.
-                match self.laser.direction {
+                match laser.direction {
                     Direction::North => {
                         start_point.y += 10.0;
                         end_point.y -= 10.0;
@@ -521,7 +898,7 @@ impl<Message> canvas::Program<Message, Renderer> for RobotChallenge {
             }
 
             // Draw hit. A X over a tank if hit.
-            if self.hit.is_visible {
+            if let Some(hit) = &self.hit {
                 let hit_path1 = Path::line(Point::new(4.0, 4.0), Point::new(16.0, 16.0));
                 let hit_path2 = Path::line(Point::new(16.0, 4.0), Point::new(4.0, 16.0));
 
@@ -534,12 +911,27 @@ impl<Message> canvas::Program<Message, Renderer> for RobotChallenge {
                     }
                 };
 
-                let x = (self.hit.point.x * 20) as f32;
-                let y = (self.hit.point.y * 20) as f32;
+                let x = (hit.x * 20) as f32;
+                let y = (hit.y * 20) as f32;
                 frame.translate(Vector::new(x, y));
                 frame.stroke(&hit_path1, hit_stroke());
                 frame.stroke(&hit_path2, hit_stroke());
             }
+
+            // Draw the result over the board when the game is over.
+            if let Some(result) = self.game_over_text() {
+                let overlay = Path::rectangle(Point::new(0.0, 0.0), frame.size());
+                frame.fill(&overlay, Color { a: 0.6, ..Color::WHITE });
+                frame.fill_text(canvas::Text {
+                    content: result,
+                    position: frame.center(),
+                    color: Color::BLACK,
+                    size: 30.0,
+                    horizontal_alignment: alignment::Horizontal::Center,
+                    vertical_alignment: alignment::Vertical::Center,
+                    ..canvas::Text::default()
+                });
+            }
         });
 
         vec![board]
@@ -609,8 +1001,21 @@ impl GameColors {
         a: 1.0,
     };
 
-    fn get_tank_colors() -> Vec<Color> {
-        vec![Self::GREEN, Self::RED, Self::BLUE, Self::TOMATO, Self::PERU, Self::AQUA, Self::PINK]
+    const SHADE: Color = Color {
+        r: 0.0,
+        g: 0.0,
+        b: 0.0,
+        a: 0.3,
+    };
+
+    const TANK_COLORS: [Color; 7] = [Self::GREEN, Self::RED, Self::BLUE, Self::TOMATO, Self::PERU, Self::AQUA, Self::PINK];
+
+    // Use the configured color if there is one, else cycle through the default tank colors.
+    fn get_tank_color(index: usize, color: Option<TankColor>) -> Color {
+        match color {
+            Some(color) => Color::from_rgb8(color.r, color.g, color.b),
+            None => Self::TANK_COLORS[index % Self::TANK_COLORS.len()],
+        }
     }
 }
 
@@ -628,227 +1033,3 @@ fn tank_path() -> Path {
     builder.close();
     builder.build()
 }
-
-#[derive(Default, Debug)]
-struct Hit {
-    point: BoardPoint,
-    is_visible: bool,
-}
-
-#[derive(Default, Debug)]
-struct Laser {
-    point: BoardPoint,
-    direction: Direction,
-    length: usize,
-    hit: bool,
-    is_visible: bool,
-}
-
-struct Tank {
-    strategy: Box<dyn Strategy>,
-    color: Color,
-    energy: usize,
-    hits: usize,
-    frags: usize,
-    point: BoardPoint,
-    // Set to random available Point when adding to Board.
-    direction: Direction, // Set to random direction when adding to Board.
-}
-
-impl Debug for Tank {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Tank(strategy: {}, energy: {}, hits: {}, frags: {:?}, {:?}, direction: {:?}",
-               self.strategy.name(), self.energy, self.hits, self.frags, self.point, self.direction)
-    }
-}
-
-impl Tank {
-    fn is_alive(&self) -> bool {
-        self.energy > 0
-    }
-}
-
-impl Default for Tank {
-    fn default() -> Self {
-        Self {
-            strategy: Box::new(Random::default()),
-            color: GameColors::GREEN,
-            energy: Self::MAX_ENERGY,
-            hits: 0,
-            frags: 0,
-            point: Default::default(),
-            direction: Default::default(),
-        }
-    }
-}
-
-impl Tank {
-    const MAX_ENERGY: usize = 5;
-    const FIRE_RANGE: usize = 5;
-}
-
-#[derive(PartialEq, Debug, Clone)]
-pub(crate) enum Move {
-    Fire,
-    TurnLeft,
-    Forward,
-    TurnRight,
-    Wait,
-}
-
-impl Distribution<Move> for Standard {
-    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Move {
-        match rng.gen_range(0..=4) {
-            0 => Move::Fire,
-            1 => Move::TurnLeft,
-            2 => Move::Forward,
-            3 => Move::TurnRight,
-            _ => Move::Wait,
-        }
-    }
-}
-
-#[derive(Debug, Clone)]
-pub(crate) struct Dimension {
-    pub(crate) width: usize,
-    pub(crate) height: usize,
-}
-
-impl Default for Dimension {
-    fn default() -> Self {
-        Self { width: 20, height: 20 }
-    }
-}
-
-#[derive(Debug, Clone, PartialEq)]
-pub(crate) struct BoardPoint {
-    pub(crate) x: isize,
-    pub(crate) y: isize,
-}
-
-impl Default for BoardPoint {
-    fn default() -> Self {
-        Self {
-            x: 0,
-            y: 0,
-        }
-    }
-}
-
-impl BoardPoint {
-    pub(crate) fn with_offset(&self, direction: Direction, offset: isize) -> Self {
-        Self {
-            x: self.x + direction.x() * offset,
-            y: self.y + direction.y() * offset
-        }
-    }
-    
-    fn random(dimension: &Dimension) -> Self {
-        Self {
-            x: rand::thread_rng().gen_range(0..dimension.width as isize),
-            y: rand::thread_rng().gen_range(0..dimension.height as isize),
-        }
-    }
-
-    fn get_unique_random_vec(len: usize, dimension: &Dimension) -> Vec<Self> {
-        let mut points: Vec<Self> = Vec::new();
-        while points.len() < len {
-            let point = Self::random(&dimension);
-            if !points.contains(&point) {
-                points.push(point);
-            }
-        }
-        points
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub(crate) enum Direction {
-    North,
-    East,
-    South,
-    West,
-}
-
-impl Direction {
-    fn degrees(&self) -> f32 {
-        match *self {
-            Self::North => 0.0,
-            Self::East => 90.0,
-            Self::South => 180.0,
-            Self::West => 270.0,
-        }
-    }
-
-    fn x(&self) -> isize {
-        match *self {
-            Self::North => 0,
-            Self::East => 1,
-            Self::South => 0,
-            Self::West => -1,
-        }
-    }
-
-    fn y(&self) -> isize {
-        match *self {
-            Self::North => -1,
-            Self::East => 0,
-            Self::South => 1,
-            Self::West => 0,
-        }
-    }
-
-    pub(crate) fn clockwise(&self) -> Direction {
-        match *self {
-            Self::North => Self::East,
-            Self::East => Self::South,
-            Self::South => Self::West,
-            Self::West => Self::North,
-        }
-    }
-
-    pub(crate) fn counter_clockwise(&self) -> Direction {
-        match *self {
-            Self::North => Self::West,
-            Self::East => Self::North,
-            Self::South => Self::East,
-            Self::West => Self::South,
-        }
-    }
-
-    fn opposite(&self) -> Direction {
-        match *self {
-            Self::North => Self::South,
-            Self::East => Self::West,
-            Self::South => Self::North,
-            Self::West => Self::East,
-        }
-    }
-}
-
-impl Default for Direction {
-    fn default() -> Self {
-        Direction::North
-    }
-}
-
-#[derive(Default, Debug)]
-pub(crate) struct TankStatus {
-    pub(crate) direction: Direction,
-    pub(crate) location: BoardPoint,
-    pub(crate) is_alive: bool,
-}
-
-#[derive(Debug, Default)]
-pub(crate) struct NextMoveInput {
-    pub(crate) game_board: Dimension,
-    pub(crate) own_status: TankStatus,
-    pub(crate) opponent_status: Vec<TankStatus>,
-    pub(crate) fire_range: usize,
-}
-
-pub(crate) trait Strategy {
-    fn name(&self) -> String;
-    fn author(&self) -> String;
-    fn next_move(&mut self, input: NextMoveInput) -> Move;
-}
\ No newline at end of file
@@ -0,0 +1,344 @@
+use std::panic;
+use std::panic::AssertUnwindSafe;
+use std::sync::mpsc;
+use std::sync::mpsc::{
+    Receiver,
+    RecvTimeoutError,
+    Sender
+};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::{
+    Duration,
+    Instant
+};
+use serde::{
+    Deserialize,
+    Serialize
+};
+use crate::engine::{
+    AbortHandle,
+    Event,
+    Move,
+    NextMoveInput,
+    Strategy
+};
+
+
+// How long dropping a host waits for the strategy to finish its calls and clean up.
+const CLEANUP_TIME: Duration = Duration::from_secs(1);
+
+// Runs a strategy on its own thread so a slow move can be abandoned and a panic doesn't take the game down.
+// A call that timed out keeps running, the strategy is only asked again once it has returned.
+pub(crate) struct StrategyHost {
+    name: String,
+    author: String,
+    // None once the strategy is disqualified.
+    calls: Option<Sender<(u64, Call)>>,
+    replies: Receiver<(u64, Result<Option<Move>, String>)>,
+    next_id: u64,
+    // Id of the last call that hasn't replied yet.
+    pending: Option<u64>,
+    used: Duration,
+    thread: Option<JoinHandle<()>>,
+    abort: Option<AbortHandle>,
+}
+
+enum Call {
+    Seed(u64),
+    Event(Event),
+    NextMove(NextMoveInput),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum FaultKind {
+    // The move took longer than the move time limit.
+    Timeout,
+    // The match time budget was used up.
+    OutOfTime,
+    // The strategy hasn't returned from a move that timed out earlier.
+    Busy,
+    Panic { message: String },
+    Disqualified,
+}
+
+impl StrategyHost {
+    pub(crate) fn new(strategy: Box<dyn Strategy>) -> Self {
+        let name = strategy.name();
+        let author = strategy.author();
+        let abort = strategy.abort_handle();
+        let (call_sender, call_receiver) = mpsc::channel::<(u64, Call)>();
+        let (reply_sender, reply_receiver) = mpsc::channel();
+        let thread = thread::spawn(move || {
+            let mut strategy = strategy;
+            for (id, call) in call_receiver {
+                let result = panic::catch_unwind(AssertUnwindSafe(|| match call {
+                    Call::Seed(seed) => {
+                        strategy.seed(seed);
+                        None
+                    }
+                    Call::Event(event) => {
+                        event.deliver(strategy.as_mut());
+                        None
+                    }
+                    Call::NextMove(input) => Some(strategy.next_move(input)),
+                }));
+                let reply = result.map_err(|payload| panic_message(payload.as_ref()));
+                if reply_sender.send((id, reply)).is_err() {
+                    break;
+                }
+            }
+        });
+        Self {
+            name,
+            author,
+            calls: Some(call_sender),
+            replies: reply_receiver,
+            next_id: 0,
+            pending: None,
+            used: Duration::ZERO,
+            thread: Some(thread),
+            abort,
+        }
+    }
+
+    pub(crate) fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub(crate) fn author(&self) -> String {
+        self.author.clone()
+    }
+
+    // Seeding is not timed, a slow seed shows up as a slow first move.
+    pub(crate) fn seed(&mut self, seed: u64) {
+        self.send(Call::Seed(seed));
+    }
+
+    // An event has the move time limit but doesn't count toward the match time. It is waited for before the
+    // next move is asked, so a slow event doesn't take time from the move. An event that runs out of time keeps
+    // running and the next move is Busy. Without a limit, or while a call is still running, the event is only
+    // queued. A panic in an event is caught and otherwise ignored.
+    pub(crate) fn notify(&mut self, event: Event, limit: Option<Duration>) {
+        self.collect_late_replies();
+        let Some(id) = self.send(Call::Event(event)) else {
+            return;
+        };
+        if self.pending.is_none() && limit.is_some() {
+            self.pending = Some(id);
+            self.wait_for(id, limit);
+        }
+    }
+
+    // The move time limit applies to this move, the match limit to the time used by all moves together.
+    pub(crate) fn next_move(&mut self, input: NextMoveInput, move_limit: Option<Duration>, match_limit: Option<Duration>)
+                            -> Result<Move, FaultKind> {
+        if self.calls.is_none() {
+            return Err(FaultKind::Disqualified);
+        }
+        self.collect_late_replies();
+        if self.pending.is_some() {
+            return Err(FaultKind::Busy);
+        }
+        let remaining = match match_limit {
+            Some(limit) if self.used >= limit => return Err(FaultKind::OutOfTime),
+            Some(limit) => Some(limit - self.used),
+            None => None,
+        };
+        let limit = match (move_limit, remaining) {
+            (Some(move_limit), Some(remaining)) => Some(move_limit.min(remaining)),
+            (move_limit, remaining) => move_limit.or(remaining),
+        };
+
+        let start = Instant::now();
+        let Some(id) = self.send(Call::NextMove(input)) else {
+            return Err(FaultKind::Disqualified);
+        };
+        self.pending = Some(id);
+        let result = self.wait_for(id, limit);
+        self.used += start.elapsed();
+        match result {
+            Some(Ok(next_move)) => Ok(next_move.unwrap_or(Move::Wait)),
+            Some(Err(message)) => Err(FaultKind::Panic { message }),
+            None if remaining.is_some_and(|remaining| Some(remaining) == limit) => Err(FaultKind::OutOfTime),
+            None => Err(FaultKind::Timeout),
+        }
+    }
+
+    // Stop calling the strategy. Its thread ends after any call still running, a call that timed out is aborted.
+    pub(crate) fn disqualify(&mut self) {
+        self.calls = None;
+        self.collect_late_replies();
+        if self.pending.is_some() {
+            self.abort();
+        }
+    }
+
+    fn abort(&mut self) {
+        if let Some(abort) = self.abort.take() {
+            abort();
+        }
+    }
+
+    fn send(&mut self, call: Call) -> Option<u64> {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.calls.as_ref()?.send((id, call)).ok()?;
+        Some(id)
+    }
+
+    // None if the limit passed first. A strategy thread that is gone counts as a panic.
+    fn wait_for(&mut self, id: u64, limit: Option<Duration>) -> Option<Result<Option<Move>, String>> {
+        let deadline = limit.map(|limit| Instant::now() + limit);
+        loop {
+            let reply = match deadline {
+                Some(deadline) => self.replies.recv_timeout(deadline.saturating_duration_since(Instant::now())),
+                None => self.replies.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match reply {
+                Ok((reply_id, result)) if reply_id == id => {
+                    self.pending = None;
+                    return Some(result);
+                }
+                Ok(_) => {}
+                Err(RecvTimeoutError::Timeout) => return None,
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.pending = None;
+                    return Some(Err("strategy thread stopped".to_string()));
+                }
+            }
+        }
+    }
+
+    fn collect_late_replies(&mut self) {
+        while let Ok((id, _)) = self.replies.try_recv() {
+            if Some(id) == self.pending {
+                self.pending = None;
+            }
+        }
+    }
+}
+
+impl Drop for StrategyHost {
+    // Wait for the strategy thread to finish the calls still queued, like the end of game event, and drop the
+    // strategy so it can clean up. A strategy that takes longer than CLEANUP_TIME is aborted and left to end.
+    fn drop(&mut self) {
+        self.calls = None;
+        let deadline = Instant::now() + CLEANUP_TIME;
+        // The thread drops its reply sender when it ends.
+        loop {
+            match self.replies.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(_) => {}
+                Err(RecvTimeoutError::Timeout) => {
+                    self.abort();
+                    return;
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Sleeps in every move and round start, panics in moves if told to.
+    struct Sleeper {
+        delay: Duration,
+        panics: bool,
+    }
+
+    impl Strategy for Sleeper {
+        fn name(&self) -> String {
+            "Sleeper".to_string()
+        }
+
+        fn author(&self) -> String {
+            "Test".to_string()
+        }
+
+        fn next_move(&mut self, _input: NextMoveInput) -> Move {
+            thread::sleep(self.delay);
+            if self.panics {
+                panic!("out of ideas");
+            }
+            Move::Fire
+        }
+
+        fn on_round_start(&mut self, _round: usize) {
+            thread::sleep(self.delay);
+        }
+    }
+
+    fn host(delay_ms: u64, panics: bool) -> StrategyHost {
+        StrategyHost::new(Box::new(Sleeper { delay: Duration::from_millis(delay_ms), panics }))
+    }
+
+    fn ms(ms: u64) -> Option<Duration> {
+        Some(Duration::from_millis(ms))
+    }
+
+    #[test]
+    fn move_within_the_limits_is_played() {
+        let mut host = host(0, false);
+        assert_eq!(host.next_move(NextMoveInput::default(), ms(1000), ms(1000)), Ok(Move::Fire));
+    }
+
+    #[test]
+    fn slow_move_times_out_and_is_busy_until_it_returns() {
+        let mut host = host(100, false);
+        assert_eq!(host.next_move(NextMoveInput::default(), ms(10), None), Err(FaultKind::Timeout));
+        assert_eq!(host.next_move(NextMoveInput::default(), ms(10), None), Err(FaultKind::Busy));
+        thread::sleep(Duration::from_millis(200));
+        assert_eq!(host.next_move(NextMoveInput::default(), None, None), Ok(Move::Fire));
+    }
+
+    #[test]
+    fn match_time_runs_out() {
+        let mut host = host(50, false);
+        assert_eq!(host.next_move(NextMoveInput::default(), None, ms(10)), Err(FaultKind::OutOfTime));
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(host.next_move(NextMoveInput::default(), None, ms(10)), Err(FaultKind::OutOfTime));
+    }
+
+    #[test]
+    fn panic_is_a_fault_with_its_message() {
+        let mut host = host(0, true);
+        match host.next_move(NextMoveInput::default(), ms(1000), None) {
+            Err(FaultKind::Panic { message }) => assert!(message.contains("out of ideas"), "{}", message),
+            other => panic!("expected a panic fault, got {:?}", other),
+        }
+        // The strategy is asked again after a panic.
+        assert!(matches!(host.next_move(NextMoveInput::default(), ms(1000), None), Err(FaultKind::Panic { .. })));
+    }
+
+    #[test]
+    fn slow_event_makes_the_next_move_busy_without_using_match_time() {
+        let mut host = host(100, false);
+        host.notify(Event::RoundStart(1), ms(10));
+        assert_eq!(host.next_move(NextMoveInput::default(), ms(10), ms(10)), Err(FaultKind::Busy));
+        assert_eq!(host.used, Duration::ZERO);
+    }
+
+    #[test]
+    fn disqualified_strategy_is_not_asked() {
+        let mut host = host(0, false);
+        host.disqualify();
+        assert_eq!(host.next_move(NextMoveInput::default(), None, None), Err(FaultKind::Disqualified));
+    }
+}
@@ -1,17 +1,28 @@
+use crate::cli::Cli;
 
 
-use iced::{
-    Application,
-    Settings
-};
-use crate::game::RobotChallenge;
-
-
+mod cli;
+mod compare;
+mod config;
+mod dylib;
+mod engine;
+mod host;
+mod process;
+mod protocol;
+mod ratings;
+mod registry;
+mod remote;
+mod replay;
+mod script;
 mod strategies;
+mod tournament;
+mod wasm;
 mod game;
 
 
-fn main() -> iced::Result {
-    RobotChallenge::run(Settings::default())
+fn main() {
+    if let Err(error) = Cli::parse_args().run() {
+        eprintln!("Error: {}", error);
+        std::process::exit(1);
+    }
 }
-
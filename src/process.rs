@@ -0,0 +1,190 @@
+use std::fmt::{
+    Display,
+    Formatter
+};
+use std::io::BufReader;
+use std::sync::mpsc;
+use std::sync::{
+    Arc,
+    Mutex
+};
+use std::thread;
+use std::process::{
+    Child,
+    ChildStdin,
+    ChildStdout,
+    Command,
+    Stdio
+};
+use crate::engine::{
+    AbortHandle,
+    Move,
+    NextMoveInput,
+    Strategy
+};
+use crate::protocol::{
+    Connection,
+    HANDSHAKE_TIME,
+    HelloReply,
+    MoveReply,
+    PROTOCOL_VERSION,
+    ProtocolError,
+    Request,
+    SeedReply
+};
+use crate::registry::{
+    FailedStrategy,
+    Registry,
+    StrategyKind
+};
+
+
+// A strategy played by an executable speaking the line protocol on its stdin and stdout.
+// Its stderr is passed through for debug output.
+pub(crate) struct ProcessStrategy {
+    command: String,
+    // Shared with the abort handle, which kills the bot when it is stuck.
+    child: Arc<Mutex<Child>>,
+    connection: Option<Connection<BufReader<ChildStdout>, ChildStdin>>,
+    name: String,
+    author: String,
+}
+
+impl ProcessStrategy {
+    // The command is split on whitespace, the first word is the executable. No shell is involved.
+    pub(crate) fn spawn(command: &str) -> Result<Self, ProcessError> {
+        let mut words = command.split_whitespace();
+        let program = words.next().ok_or_else(|| ProcessError::Spawn(command.to_string(), "empty command".to_string()))?;
+        let mut child = Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|error| ProcessError::Spawn(command.to_string(), error.to_string()))?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+
+        let mut strategy = Self {
+            command: command.to_string(),
+            child: Arc::new(Mutex::new(child)),
+            connection: None,
+            name: String::new(),
+            author: String::new(),
+        };
+        let handshake_error = |error: ProtocolError| ProcessError::Handshake(command.to_string(), error);
+
+        // The reply is read on another thread, so a bot that doesn't answer can't hang the caller. Dropping the
+        // strategy then kills the bot, which ends the read.
+        let mut connection = Connection::new(BufReader::new(stdout), stdin);
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let reply = connection.request::<HelloReply>(&Request::Hello { version: PROTOCOL_VERSION });
+            let _ = sender.send((connection, reply));
+        });
+        let (connection, reply) = receiver.recv_timeout(HANDSHAKE_TIME)
+            .map_err(|_| handshake_error(ProtocolError::Timeout))?;
+        let hello = reply.map_err(handshake_error)?;
+        strategy.connection = Some(connection);
+        strategy.name = hello.name;
+        strategy.author = hello.author;
+        Ok(strategy)
+    }
+
+    // After the first error the bot is dropped and every later request fails.
+    fn request<T: serde::de::DeserializeOwned>(&mut self, request: &Request) -> Result<T, ProtocolError> {
+        let connection = self.connection.as_mut().ok_or(ProtocolError::Closed)?;
+        let reply = connection.request(request);
+        if let Err(error) = &reply {
+            eprintln!("Bot '{}' failed: {}", self.command, error);
+            self.connection = None;
+        }
+        reply
+    }
+}
+
+impl Strategy for ProcessStrategy {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn author(&self) -> String {
+        self.author.clone()
+    }
+
+    // A failed request panics, so the strategy host records it as a fault.
+    fn next_move(&mut self, input: NextMoveInput) -> Move {
+        match self.request::<MoveReply>(&Request::NextMove { input }) {
+            Ok(reply) => reply.next_move,
+            Err(error) => panic!("bot '{}': {}", self.command, error),
+        }
+    }
+
+    fn seed(&mut self, seed: u64) {
+        if let Err(error) = self.request::<SeedReply>(&Request::Seed { seed }) {
+            panic!("bot '{}': {}", self.command, error);
+        }
+    }
+
+    fn abort_handle(&self) -> Option<AbortHandle> {
+        let child = self.child.clone();
+        Some(Box::new(move || {
+            let _ = child.lock().unwrap().kill();
+        }))
+    }
+}
+
+impl Drop for ProcessStrategy {
+    fn drop(&mut self) {
+        let mut child = self.child.lock().unwrap();
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+// Register a bot from "ID=COMMAND". The bot is started once to check the handshake, and that process
+// is the instance the registry reads name and author from.
+pub(crate) fn register(registry: &mut Registry, bot: &str) -> Result<(), ProcessError> {
+    let (id, command) = bot.split_once('=').ok_or_else(|| ProcessError::InvalidBot(bot.to_string()))?;
+    let (id, command) = (id.trim().to_string(), command.trim().to_string());
+    if id.is_empty() {
+        return Err(ProcessError::InvalidBot(bot.to_string()));
+    }
+    let probe = ProcessStrategy::spawn(&command)?;
+    let (name, author) = (probe.name(), probe.author());
+    let probe = Mutex::new(Some(probe));
+
+    let description = format!("External process: {}", command);
+    registry.register_kind(&id, &description, StrategyKind::Process, move || -> Box<dyn Strategy> {
+        if let Some(probe) = probe.lock().unwrap().take() {
+            return Box::new(probe);
+        }
+        match ProcessStrategy::spawn(&command) {
+            Ok(strategy) => Box::new(strategy),
+            Err(error) => {
+                eprintln!("{}", error);
+                Box::new(FailedStrategy::new(&name, &author, &error.to_string()))
+            }
+        }
+    });
+    Ok(())
+}
+
+#[derive(Debug)]
+pub(crate) enum ProcessError {
+    InvalidBot(String),
+    Spawn(String, String),
+    Handshake(String, ProtocolError),
+}
+
+impl Display for ProcessError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidBot(bot) => write!(f, "invalid bot '{}', expected ID=COMMAND", bot),
+            Self::Spawn(command, error) => write!(f, "can't start bot '{}': {}", command, error),
+            Self::Handshake(command, error) => write!(f, "bot '{}' failed the handshake: {}", command, error),
+        }
+    }
+}
+
+impl std::error::Error for ProcessError {}
@@ -0,0 +1,120 @@
+use std::fmt::{
+    Display,
+    Formatter
+};
+use std::io;
+use std::time::Duration;
+use std::io::{
+    BufRead,
+    Write
+};
+use serde::de::DeserializeOwned;
+use serde::{
+    Deserialize,
+    Serialize
+};
+use crate::engine::{
+    Move,
+    NextMoveInput
+};
+
+
+// Line protocol for bots outside the process. Every message is one line of JSON. The host sends a request
+// and the bot answers each with one line:
+//
+//   {"type":"hello","version":1}          -> {"name":"MyBot","author":"Me"}
+//   {"type":"seed","seed":123}            -> {}
+//   {"type":"next_move","input":{...}}    -> {"move":"Fire"}
+//
+// Moves are "Fire", "TurnLeft", "Forward", "TurnRight" or "Wait". The input is NextMoveInput as in replays,
+// its version field tells which fields it has, see INPUT_VERSION.
+// Process bots get the requests on stdin, remote bots on a TCP connection to a game started with --listen.
+pub(crate) const PROTOCOL_VERSION: u32 = 1;
+
+// How long a bot has to answer hello.
+pub(crate) const HANDSHAKE_TIME: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum Request {
+    Hello { version: u32 },
+    Seed { seed: u64 },
+    NextMove { input: NextMoveInput },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct HelloReply {
+    pub(crate) name: String,
+    pub(crate) author: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct SeedReply {}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct MoveReply {
+    #[serde(rename = "move")]
+    pub(crate) next_move: Move,
+}
+
+pub(crate) struct Connection<R: BufRead, W: Write> {
+    reader: R,
+    writer: W,
+}
+
+impl<R: BufRead, W: Write> Connection<R, W> {
+    pub(crate) fn new(reader: R, writer: W) -> Self {
+        Self { reader, writer }
+    }
+
+    pub(crate) fn request<T: DeserializeOwned>(&mut self, request: &Request) -> Result<T, ProtocolError> {
+        self.send(request)?;
+        self.receive()
+    }
+
+    pub(crate) fn send<T: Serialize>(&mut self, message: &T) -> Result<(), ProtocolError> {
+        serde_json::to_writer(&mut self.writer, message).map_err(|error| ProtocolError::Io(error.to_string()))?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    pub(crate) fn receive<T: DeserializeOwned>(&mut self) -> Result<T, ProtocolError> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(ProtocolError::Closed);
+        }
+        serde_json::from_str(line.trim()).map_err(|error| ProtocolError::Invalid(line.trim().to_string(), error.to_string()))
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum ProtocolError {
+    Io(String),
+    Closed,
+    Invalid(String, String),
+    Timeout,
+}
+
+impl From<io::Error> for ProtocolError {
+    // A read timeout is WouldBlock on Unix and TimedOut on Windows.
+    fn from(error: io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => Self::Timeout,
+            _ => Self::Io(error.to_string()),
+        }
+    }
+}
+
+impl Display for ProtocolError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{}", error),
+            Self::Closed => write!(f, "bot closed the connection"),
+            Self::Invalid(line, error) => write!(f, "invalid reply '{}': {}", line, error),
+            Self::Timeout => write!(f, "bot didn't answer in time"),
+        }
+    }
+}
+
+impl std::error::Error for ProtocolError {}
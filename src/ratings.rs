@@ -0,0 +1,190 @@
+use std::collections::BTreeMap;
+use std::fmt::{
+    Display,
+    Formatter
+};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{
+    Path,
+    PathBuf
+};
+use serde::{
+    Deserialize,
+    Serialize
+};
+
+
+pub(crate) const RATINGS_VERSION: u32 = 1;
+
+// Elo ratings per strategy id. A game with several tanks counts as one match between every pair of tanks,
+// scored by their placements.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct Ratings {
+    version: u32,
+    ratings: BTreeMap<String, Rating>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub(crate) struct Rating {
+    pub(crate) rating: f64,
+    pub(crate) games: usize,
+}
+
+impl Default for Ratings {
+    fn default() -> Self {
+        Self {
+            version: RATINGS_VERSION,
+            ratings: BTreeMap::new(),
+        }
+    }
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Self {
+            rating: Ratings::INITIAL_RATING,
+            games: 0,
+        }
+    }
+}
+
+impl Ratings {
+    const INITIAL_RATING: f64 = 1500.0;
+    const K_FACTOR: f64 = 32.0;
+
+    // A missing file gives empty ratings, so the first run creates it.
+    pub(crate) fn load(path: &Path) -> Result<Self, RatingsError> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(error) => return Err(RatingsError::Read(path.to_path_buf(), error.to_string())),
+        };
+        let ratings: Self = serde_json::from_str(&content)
+            .map_err(|error| RatingsError::Parse(path.to_path_buf(), error.to_string()))?;
+        if ratings.version != RATINGS_VERSION {
+            return Err(RatingsError::UnsupportedVersion(path.to_path_buf(), ratings.version));
+        }
+        Ok(ratings)
+    }
+
+    pub(crate) fn save(&self, path: &Path) -> Result<(), RatingsError> {
+        let content = serde_json::to_string_pretty(self).unwrap();
+        fs::write(path, content + "\n").map_err(|error| RatingsError::Write(path.to_path_buf(), error.to_string()))
+    }
+
+    // Update from one game given the strategy id and place of every tank. A strategy with several tanks
+    // is rated once, by its best place, so it plays every other strategy once and counts one game.
+    pub(crate) fn update(&mut self, placements: &[(&str, usize)]) {
+        let mut best: BTreeMap<&str, usize> = BTreeMap::new();
+        for &(id, place) in placements {
+            let entry = best.entry(id).or_insert(place);
+            *entry = (*entry).min(place);
+        }
+        if best.len() < 2 {
+            return;
+        }
+        let rating = |id: &str| self.ratings.get(id).copied().unwrap_or_default().rating;
+        let opponents = (best.len() - 1) as f64;
+        let deltas: Vec<(&str, f64)> = best.iter()
+            .map(|(&id, &place)| {
+                let score: f64 = best.iter()
+                    .filter(|&(&other, _)| other != id)
+                    .map(|(&other, &other_place)| {
+                        let actual = match place.cmp(&other_place) {
+                            std::cmp::Ordering::Less => 1.0,
+                            std::cmp::Ordering::Equal => 0.5,
+                            std::cmp::Ordering::Greater => 0.0,
+                        };
+                        let expected = 1.0 / (1.0 + 10f64.powf((rating(other) - rating(id)) / 400.0));
+                        actual - expected
+                    })
+                    .sum();
+                (id, Self::K_FACTOR * score / opponents)
+            })
+            .collect();
+
+        for (id, delta) in deltas {
+            let entry = self.ratings.entry(id.to_string()).or_default();
+            entry.rating += delta;
+            entry.games += 1;
+        }
+    }
+
+    // Best rated first.
+    pub(crate) fn leaderboard(&self) -> Vec<(&str, Rating)> {
+        let mut leaderboard: Vec<(&str, Rating)> = self.ratings.iter()
+            .map(|(id, rating)| (id.as_str(), *rating))
+            .collect();
+        leaderboard.sort_by(|a, b| b.1.rating.total_cmp(&a.1.rating));
+        leaderboard
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum RatingsError {
+    Read(PathBuf, String),
+    Parse(PathBuf, String),
+    UnsupportedVersion(PathBuf, u32),
+    Write(PathBuf, String),
+}
+
+impl Display for RatingsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Read(path, error) => write!(f, "can't read ratings {}: {}", path.display(), error),
+            Self::Parse(path, error) => write!(f, "can't parse ratings {}: {}", path.display(), error),
+            Self::UnsupportedVersion(path, version) => write!(f, "unsupported ratings version {} in {}", version,
+                                                              path.display()),
+            Self::Write(path, error) => write!(f, "can't write ratings {}: {}", path.display(), error),
+        }
+    }
+}
+
+impl std::error::Error for RatingsError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rating(ratings: &Ratings, id: &str) -> Rating {
+        ratings.ratings[id]
+    }
+
+    #[test]
+    fn winner_gains_what_loser_loses() {
+        let mut ratings = Ratings::default();
+        ratings.update(&[("a", 1), ("b", 2)]);
+        assert_eq!(rating(&ratings, "a").rating, 1516.0);
+        assert_eq!(rating(&ratings, "b").rating, 1484.0);
+        assert_eq!(rating(&ratings, "a").games, 1);
+    }
+
+    #[test]
+    fn shared_place_is_a_draw() {
+        let mut ratings = Ratings::default();
+        ratings.update(&[("a", 1), ("b", 1)]);
+        assert_eq!(rating(&ratings, "a").rating, 1500.0);
+        assert_eq!(rating(&ratings, "b").rating, 1500.0);
+    }
+
+    #[test]
+    fn repeated_strategy_is_rated_once_by_its_best_place() {
+        let mut repeated = Ratings::default();
+        repeated.update(&[("b", 3), ("a", 2), ("b", 1), ("b", 4)]);
+        let mut single = Ratings::default();
+        single.update(&[("b", 1), ("a", 2)]);
+
+        for id in ["a", "b"] {
+            assert_eq!(rating(&repeated, id).rating, rating(&single, id).rating);
+            assert_eq!(rating(&repeated, id).games, 1);
+        }
+    }
+
+    #[test]
+    fn game_with_one_strategy_changes_nothing() {
+        let mut ratings = Ratings::default();
+        ratings.update(&[("a", 1), ("a", 2)]);
+        assert!(ratings.ratings.is_empty());
+    }
+}
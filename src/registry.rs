@@ -0,0 +1,131 @@
+use crate::engine::{
+    Move,
+    NextMoveInput,
+    Strategy
+};
+use crate::strategies;
+
+
+pub(crate) type StrategyFactory = Box<dyn Fn() -> Box<dyn Strategy> + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum StrategyKind {
+    // Every instance is independent, any number can play at the same time.
+    Local,
+    // Runs in a bot process, which can stop answering without the game noticing.
+    Process,
+    // All instances share the bot's one connection, so only one may play at a time. It can stop answering like a
+    // process.
+    Remote,
+}
+
+pub(crate) struct RegistryEntry {
+    pub(crate) id: String,
+    pub(crate) name: String,
+    pub(crate) author: String,
+    pub(crate) description: String,
+    pub(crate) kind: StrategyKind,
+    factory: StrategyFactory,
+}
+
+impl RegistryEntry {
+    pub(crate) fn create(&self) -> Box<dyn Strategy> {
+        (self.factory)()
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Registry {
+    entries: Vec<RegistryEntry>,
+}
+
+impl Registry {
+    pub(crate) fn builtin() -> Self {
+        let mut registry = Self::default();
+        strategies::register(&mut registry);
+        registry
+    }
+
+    pub(crate) fn register<F>(&mut self, id: &str, description: &str, factory: F)
+        where
+            F: Fn() -> Box<dyn Strategy> + Send + Sync + 'static
+    {
+        self.register_kind(id, description, StrategyKind::Local, factory);
+    }
+
+    // Name and author are read from a throwaway instance so they always match what the strategy reports.
+    pub(crate) fn register_kind<F>(&mut self, id: &str, description: &str, kind: StrategyKind, factory: F)
+        where
+            F: Fn() -> Box<dyn Strategy> + Send + Sync + 'static
+    {
+        let strategy = factory();
+        let entry = RegistryEntry {
+            id: id.to_string(),
+            name: strategy.name(),
+            author: strategy.author(),
+            description: description.to_string(),
+            kind,
+            factory: Box::new(factory),
+        };
+        match self.entries.iter_mut().find(|existing| existing.id == entry.id) {
+            Some(existing) => *existing = entry,
+            None => self.entries.push(entry),
+        }
+    }
+
+    pub(crate) fn entries(&self) -> &[RegistryEntry] {
+        &self.entries
+    }
+
+    // Look up by id first, then by strategy name. Both are case-insensitive.
+    pub(crate) fn lookup(&self, key: &str) -> Option<&RegistryEntry> {
+        self.entries.iter().find(|entry| entry.id.eq_ignore_ascii_case(key))
+            .or_else(|| self.entries.iter().find(|entry| entry.name.eq_ignore_ascii_case(key)))
+    }
+
+    pub(crate) fn by_author<'a>(&'a self, author: &'a str) -> impl Iterator<Item = &'a RegistryEntry> {
+        self.entries.iter().filter(move |entry| entry.author.eq_ignore_ascii_case(author))
+    }
+
+    pub(crate) fn create(&self, key: &str) -> Option<Box<dyn Strategy>> {
+        self.lookup(key).map(|entry| entry.create())
+    }
+
+    // Kinds of the registered strategies among the keys.
+    pub(crate) fn kinds<'a>(&'a self, keys: impl IntoIterator<Item = &'a String> + 'a)
+                            -> impl Iterator<Item = StrategyKind> + 'a {
+        keys.into_iter().filter_map(|key| self.lookup(key)).map(|entry| entry.kind)
+    }
+}
+
+// Stands in for a strategy that worked when it was registered but couldn't be created for a game.
+// Every move panics, so the strategy host records a fault.
+pub(crate) struct FailedStrategy {
+    name: String,
+    author: String,
+    error: String,
+}
+
+impl FailedStrategy {
+    pub(crate) fn new(name: &str, author: &str, error: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            author: author.to_string(),
+            error: error.to_string(),
+        }
+    }
+}
+
+impl Strategy for FailedStrategy {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn author(&self) -> String {
+        self.author.clone()
+    }
+
+    fn next_move(&mut self, _input: NextMoveInput) -> Move {
+        panic!("{}", self.error)
+    }
+}
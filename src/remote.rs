@@ -0,0 +1,194 @@
+use std::fmt::{
+    Display,
+    Formatter
+};
+use std::io;
+use std::io::BufReader;
+use std::net::{
+    TcpListener,
+    TcpStream
+};
+use std::sync::{
+    Arc,
+    Mutex
+};
+use crate::engine::{
+    Move,
+    NextMoveInput,
+    Strategy
+};
+use crate::protocol::{
+    Connection,
+    HANDSHAKE_TIME,
+    HelloReply,
+    MoveReply,
+    PROTOCOL_VERSION,
+    ProtocolError,
+    Request,
+    SeedReply
+};
+use crate::registry::{
+    Registry,
+    RegistryEntry,
+    StrategyKind
+};
+
+
+type TcpConnection = Connection<BufReader<TcpStream>, TcpStream>;
+
+// A bot connected over TCP, speaking the same line protocol as process bots. The game is the server: it listens,
+// and every client that connects is greeted with hello and from then on asked for moves. A bot has one connection
+// for all games, so every game starts with a seed request. It can't play two tanks at once, games with it are
+// checked to have it only once and are not played in parallel.
+pub(crate) struct RemoteStrategy {
+    bot: Arc<RemoteBot>,
+}
+
+struct RemoteBot {
+    address: String,
+    // None after the first error, every later request fails.
+    connection: Mutex<Option<TcpConnection>>,
+    name: String,
+    author: String,
+}
+
+impl RemoteBot {
+    fn handshake(stream: TcpStream) -> Result<Self, RemoteError> {
+        let address = stream.peer_addr().map_or_else(|_| "unknown address".to_string(), |address| address.to_string());
+        let error = |error: ProtocolError| RemoteError::Handshake(address.clone(), error);
+        stream.set_nodelay(true).map_err(|e| error(e.into()))?;
+        // A client that doesn't answer, like a port scan, must not block waiting for the other bots.
+        stream.set_read_timeout(Some(HANDSHAKE_TIME)).map_err(|e| error(e.into()))?;
+        let reader = BufReader::new(stream.try_clone().map_err(|e| error(e.into()))?);
+        let mut connection = Connection::new(reader, stream.try_clone().map_err(|e| error(e.into()))?);
+        let hello: HelloReply = connection.request(&Request::Hello { version: PROTOCOL_VERSION }).map_err(error)?;
+        stream.set_read_timeout(None).map_err(|e| error(e.into()))?;
+        Ok(Self {
+            address: address.clone(),
+            connection: Mutex::new(Some(connection)),
+            name: hello.name,
+            author: hello.author,
+        })
+    }
+
+    fn request<T: serde::de::DeserializeOwned>(&self, request: &Request) -> Result<T, ProtocolError> {
+        let mut connection = self.connection.lock().unwrap();
+        let reply = connection.as_mut().ok_or(ProtocolError::Closed)?.request(request);
+        if let Err(error) = &reply {
+            eprintln!("Remote bot {} at {} failed: {}", self.name, self.address, error);
+            *connection = None;
+        }
+        reply
+    }
+}
+
+impl Strategy for RemoteStrategy {
+    fn name(&self) -> String {
+        self.bot.name.clone()
+    }
+
+    fn author(&self) -> String {
+        self.bot.author.clone()
+    }
+
+    // A failed request panics, so the strategy host records it as a fault.
+    fn next_move(&mut self, input: NextMoveInput) -> Move {
+        match self.bot.request::<MoveReply>(&Request::NextMove { input }) {
+            Ok(reply) => reply.next_move,
+            Err(error) => panic!("remote bot {} at {}: {}", self.bot.name, self.bot.address, error),
+        }
+    }
+
+    fn seed(&mut self, seed: u64) {
+        if let Err(error) = self.bot.request::<SeedReply>(&Request::Seed { seed }) {
+            panic!("remote bot {} at {}: {}", self.bot.name, self.bot.address, error);
+        }
+    }
+}
+
+// Listen on the address until the given number of bots have connected and register them as remote1, remote2...
+// A client that fails the handshake is reported and doesn't count.
+pub(crate) fn accept(registry: &mut Registry, address: &str, count: usize) -> Result<(), RemoteError> {
+    let listener = TcpListener::bind(address).map_err(|error| RemoteError::Listen(address.to_string(), error))?;
+    let local = listener.local_addr().map_err(|error| RemoteError::Listen(address.to_string(), error))?;
+    println!("Waiting for {} remote bot(s) on {}", count, local);
+    let mut connected = 0;
+    while connected < count {
+        let (stream, _) = listener.accept().map_err(|error| RemoteError::Listen(address.to_string(), error))?;
+        let bot = match RemoteBot::handshake(stream) {
+            Ok(bot) => Arc::new(bot),
+            Err(error) => {
+                eprintln!("{}", error);
+                continue;
+            }
+        };
+        connected += 1;
+        let id = format!("remote{}", connected);
+        println!("{} by {} connected from {} as {}", bot.name, bot.author, bot.address, id);
+        let description = format!("Remote bot: {}", bot.address);
+        registry.register_kind(&id, &description, StrategyKind::Remote, move || -> Box<dyn Strategy> {
+            Box::new(RemoteStrategy { bot: bot.clone() })
+        });
+    }
+    Ok(())
+}
+
+// The client side: connect to a game and play a strategy until the game closes the connection.
+// Every seed request starts a new game with a fresh strategy.
+pub(crate) fn connect(address: &str, entry: &RegistryEntry) -> Result<(), RemoteError> {
+    let error = |error: ProtocolError| RemoteError::Connection(address.to_string(), error);
+    let stream = TcpStream::connect(address).map_err(|e| error(e.into()))?;
+    stream.set_nodelay(true).map_err(|e| error(e.into()))?;
+    let reader = BufReader::new(stream.try_clone().map_err(|e| error(e.into()))?);
+    let mut connection = Connection::new(reader, stream);
+    println!("Connected to {} as {}", address, entry.name);
+
+    let mut strategy = entry.create();
+    loop {
+        let request = match connection.receive::<Request>() {
+            Ok(request) => request,
+            Err(ProtocolError::Closed) => break,
+            Err(e) => return Err(error(e)),
+        };
+        match request {
+            Request::Hello { version } => {
+                if version != PROTOCOL_VERSION {
+                    return Err(RemoteError::Version(version));
+                }
+                connection.send(&HelloReply { name: strategy.name(), author: strategy.author() }).map_err(error)?;
+            }
+            Request::Seed { seed } => {
+                strategy = entry.create();
+                strategy.seed(seed);
+                connection.send(&SeedReply {}).map_err(error)?;
+            }
+            Request::NextMove { input } => {
+                let next_move = strategy.next_move(input);
+                connection.send(&MoveReply { next_move }).map_err(error)?;
+            }
+        }
+    }
+    println!("Disconnected from {}", address);
+    Ok(())
+}
+
+#[derive(Debug)]
+pub(crate) enum RemoteError {
+    Listen(String, io::Error),
+    Handshake(String, ProtocolError),
+    Connection(String, ProtocolError),
+    Version(u32),
+}
+
+impl Display for RemoteError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Listen(address, error) => write!(f, "can't listen on {}: {}", address, error),
+            Self::Handshake(address, error) => write!(f, "remote bot at {} failed the handshake: {}", address, error),
+            Self::Connection(address, error) => write!(f, "connection to {} failed: {}", address, error),
+            Self::Version(version) => write!(f, "unsupported protocol version {}, expected {}", version, PROTOCOL_VERSION),
+        }
+    }
+}
+
+impl std::error::Error for RemoteError {}
@@ -0,0 +1,307 @@
+use std::fmt::{
+    Display,
+    Formatter
+};
+use std::fs::File;
+use std::io;
+use std::io::{
+    BufRead,
+    BufReader,
+    BufWriter,
+    Write
+};
+use std::path::Path;
+use serde::{
+    Deserialize,
+    Serialize
+};
+use crate::config::MatchConfig;
+use crate::engine::{
+    BoardPoint,
+    Direction,
+    Engine,
+    Fault,
+    Hit,
+    Laser,
+    MatchResult,
+    Move,
+    NextMoveInput,
+    Rules,
+    Step
+};
+
+
+pub(crate) const REPLAY_VERSION: u32 = 1;
+
+// A replay is a JSON-lines file: one header record, then one record per engine step.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum Record {
+    Header(Header),
+    Round { round: usize },
+    Turn(Box<TurnRecord>),
+    End(MatchResult),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Header {
+    pub(crate) version: u32,
+    pub(crate) seed: u64,
+    pub(crate) rules: Rules,
+    pub(crate) tanks: Vec<TankRecord>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct TankRecord {
+    pub(crate) strategy: String,
+    pub(crate) name: String,
+    pub(crate) author: String,
+    pub(crate) color: Option<String>,
+    pub(crate) point: BoardPoint,
+    pub(crate) direction: Direction,
+    pub(crate) energy: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct TurnRecord {
+    pub(crate) round: usize,
+    pub(crate) tank: usize,
+    pub(crate) input: Option<NextMoveInput>,
+    #[serde(rename = "move")]
+    pub(crate) next_move: Move,
+    // Position of the moving tank after the move.
+    pub(crate) point: BoardPoint,
+    pub(crate) direction: Direction,
+    pub(crate) laser: Option<Laser>,
+    pub(crate) hit: Option<Hit>,
+    // Set when the strategy was too slow or panicked, and the policy that was applied.
+    #[serde(default)]
+    pub(crate) fault: Option<Fault>,
+}
+
+pub(crate) struct Recorder<W: Write> {
+    writer: W,
+}
+
+impl Recorder<BufWriter<File>> {
+    pub(crate) fn create(path: &Path, config: &MatchConfig, engine: &Engine) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?), config, engine)
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    // Writes the header, so the engine must not have been stepped yet.
+    pub(crate) fn new(writer: W, config: &MatchConfig, engine: &Engine) -> io::Result<Self> {
+        let tanks = config.roster.iter().zip(engine.tanks())
+            .map(|(entry, tank)| TankRecord {
+                strategy: entry.strategy.clone(),
+                name: tank.strategy.name(),
+                author: tank.strategy.author(),
+                color: entry.color.map(|color| color.to_string()),
+                point: tank.point.clone(),
+                direction: tank.direction,
+                energy: tank.energy,
+            })
+            .collect();
+        let header = Header {
+            version: REPLAY_VERSION,
+            seed: engine.seed(),
+            rules: engine.rules().clone(),
+            tanks,
+        };
+        let mut recorder = Self { writer };
+        recorder.write(&Record::Header(header))?;
+        Ok(recorder)
+    }
+
+    pub(crate) fn record(&mut self, step: &Step, engine: &Engine) -> io::Result<()> {
+        let record = match step {
+            Step::NewRound(round) => Record::Round { round: *round },
+            Step::Turn(turn) => {
+                let tank = &engine.tanks()[turn.index];
+                Record::Turn(Box::new(TurnRecord {
+                    round: engine.round(),
+                    tank: turn.index,
+                    input: turn.input.clone(),
+                    next_move: turn.next_move.clone(),
+                    point: tank.point.clone(),
+                    direction: tank.direction,
+                    laser: turn.laser.clone(),
+                    hit: turn.hit.clone(),
+                    fault: turn.fault.clone(),
+                }))
+            }
+            Step::GameOver(_) => Record::End(engine.result().unwrap().clone()),
+        };
+        self.write(&record)?;
+        if let Step::GameOver(_) = step {
+            self.writer.flush()?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn run_to_completion(&mut self, engine: &mut Engine) -> io::Result<Option<usize>> {
+        loop {
+            let step = engine.step();
+            self.record(&step, engine)?;
+            if let Step::GameOver(winner) = step {
+                return Ok(winner);
+            }
+        }
+    }
+
+    fn write(&mut self, record: &Record) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, record)?;
+        self.writer.write_all(b"\n")
+    }
+}
+
+// Board state after a move, rebuilt from the records of a replay file.
+#[derive(Debug, Clone)]
+pub(crate) struct Frame {
+    pub(crate) round: usize,
+    pub(crate) tanks: Vec<TankState>,
+    pub(crate) laser: Option<Laser>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct TankState {
+    pub(crate) point: BoardPoint,
+    pub(crate) direction: Direction,
+    pub(crate) energy: usize,
+    pub(crate) hits: usize,
+    pub(crate) frags: usize,
+}
+
+#[derive(Debug)]
+pub(crate) struct Replay {
+    pub(crate) header: Header,
+    pub(crate) frames: Vec<Frame>,
+    // None if the recording stopped before the game was over.
+    pub(crate) result: Option<MatchResult>,
+}
+
+impl Replay {
+    pub(crate) fn load(path: &Path) -> Result<Self, ReplayError> {
+        let file = File::open(path).map_err(|error| ReplayError::Read(error.to_string()))?;
+        let mut records = Vec::new();
+        for (index, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|error| ReplayError::Read(error.to_string()))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record: Record = serde_json::from_str(&line)
+                .map_err(|error| ReplayError::Parse { line: index + 1, message: error.to_string() })?;
+            records.push(record);
+        }
+        Self::from_records(records)
+    }
+
+    fn from_records(records: Vec<Record>) -> Result<Self, ReplayError> {
+        let mut records = records.into_iter();
+        let header = match records.next() {
+            Some(Record::Header(header)) => header,
+            _ => return Err(ReplayError::MissingHeader),
+        };
+        if header.version != REPLAY_VERSION {
+            return Err(ReplayError::UnsupportedVersion(header.version));
+        }
+
+        let mut frame = Frame {
+            round: 0,
+            tanks: header.tanks.iter()
+                .map(|tank| TankState {
+                    point: tank.point.clone(),
+                    direction: tank.direction,
+                    energy: tank.energy,
+                    hits: 0,
+                    frags: 0,
+                })
+                .collect(),
+            laser: None,
+        };
+        let mut frames = vec![frame.clone()];
+        let mut result = None;
+        for record in records {
+            match record {
+                Record::Header(_) => return Err(ReplayError::UnexpectedHeader),
+                Record::Round { round } => frame.round = round,
+                Record::Turn(turn) => {
+                    if turn.tank >= frame.tanks.len() {
+                        return Err(ReplayError::UnknownTank(turn.tank));
+                    }
+                    let tank = &mut frame.tanks[turn.tank];
+                    tank.point = turn.point;
+                    tank.direction = turn.direction;
+                    if let Some(fault) = &turn.fault {
+                        tank.energy = fault.energy;
+                    }
+                    if let Some(hit) = &turn.hit {
+                        tank.hits += 1;
+                        tank.frags += if hit.frag { 1 } else { 0 };
+                        match frame.tanks.get_mut(hit.index) {
+                            Some(hit_tank) => hit_tank.energy = hit.energy,
+                            None => return Err(ReplayError::UnknownTank(hit.index)),
+                        }
+                    }
+                    frame.round = turn.round;
+                    frame.laser = turn.laser;
+                    frames.push(frame.clone());
+                    frame.laser = None;
+                }
+                Record::End(end) => result = Some(end),
+            }
+        }
+        Ok(Self {
+            header,
+            frames,
+            result,
+        })
+    }
+
+    // Index of the first frame of the round after the one shown in frame.
+    pub(crate) fn next_round_frame(&self, frame: usize) -> usize {
+        let round = self.frames[frame].round;
+        (frame..self.frames.len()).find(|&index| self.frames[index].round > round)
+            .unwrap_or(self.frames.len() - 1)
+    }
+
+    // Index of the first frame of the round shown in frame, or of the previous round if already there.
+    pub(crate) fn previous_round_frame(&self, frame: usize) -> usize {
+        let first_of_round = |round: usize| (0..self.frames.len())
+            .find(|&index| self.frames[index].round == round)
+            .unwrap_or(0);
+        let round = self.frames[frame].round;
+        let start = first_of_round(round);
+        if start < frame || round == 0 {
+            start
+        } else {
+            first_of_round(self.frames[frame - 1].round)
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum ReplayError {
+    Read(String),
+    Parse { line: usize, message: String },
+    MissingHeader,
+    UnexpectedHeader,
+    UnsupportedVersion(u32),
+    UnknownTank(usize),
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Read(error) => write!(f, "can't read replay: {}", error),
+            Self::Parse { line, message } => write!(f, "invalid replay record on line {}: {}", line, message),
+            Self::MissingHeader => write!(f, "replay doesn't start with a header record"),
+            Self::UnexpectedHeader => write!(f, "replay has more than one header record"),
+            Self::UnsupportedVersion(version) => write!(f, "unsupported replay version {}", version),
+            Self::UnknownTank(index) => write!(f, "replay refers to unknown tank {}", index),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
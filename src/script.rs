@@ -0,0 +1,228 @@
+use std::fmt::{
+    Display,
+    Formatter
+};
+use std::fs;
+use std::path::{
+    Path,
+    PathBuf
+};
+use std::sync::{
+    Arc,
+    Mutex
+};
+use std::time::SystemTime;
+use rhai::{
+    CallFnOptions,
+    Dynamic,
+    Engine,
+    EvalAltResult,
+    FuncArgs,
+    ImmutableString,
+    Map,
+    Scope,
+    AST
+};
+use crate::engine::{
+    GameInfo,
+    MatchResult,
+    Move,
+    NextMoveInput,
+    Strategy
+};
+use crate::registry::Registry;
+
+
+// A strategy written in Rhai. The script defines
+//
+//   fn next_move(input)    input is NextMoveInput as an object map, as in replays. Returns "Fire", "TurnLeft",
+//                          "Forward", "TurnRight" or "Wait"
+//   fn name(), fn author() optional
+//   fn seed(seed)          optional
+//   fn on_game_start(info), on_round_start(round), on_round_end(round), on_hit(by), on_frag(tank),
+//   on_game_end(result)    optional, see Strategy. info and result are object maps
+//
+// Inside the functions `this` is a map that is kept for the whole game, for state between moves. The file is
+// checked before every move and reloaded when it changed, so a script can be edited while the GUI is running.
+pub(crate) struct ScriptStrategy {
+    source: Arc<Mutex<ScriptSource>>,
+    engine: Arc<Engine>,
+    ast: Arc<AST>,
+    state: Dynamic,
+    name: String,
+    author: String,
+}
+
+// Roughly the number of statements one call may run, so an endless loop ends in a fault.
+const MAX_OPERATIONS: u64 = 10_000_000;
+
+// The compiled script, shared by all games of a registered strategy.
+struct ScriptSource {
+    path: PathBuf,
+    engine: Arc<Engine>,
+    ast: Arc<AST>,
+    modified: Option<SystemTime>,
+}
+
+impl ScriptSource {
+    fn load(path: &Path) -> Result<Self, ScriptError> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        let engine = Arc::new(engine);
+        let modified = modified(path);
+        let ast = compile(&engine, path)?;
+        if !ast.iter_functions().any(|function| function.name == "next_move" && function.params.len() == 1) {
+            return Err(ScriptError(format!("{}: no function next_move(input)", path.display())));
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            engine,
+            ast: Arc::new(ast),
+            modified,
+        })
+    }
+
+    // A script that doesn't compile anymore is reported and the previous version is kept.
+    fn refresh(&mut self) -> Arc<AST> {
+        let modified = modified(&self.path);
+        if modified != self.modified {
+            self.modified = modified;
+            match compile(&self.engine, &self.path) {
+                Ok(ast) => {
+                    println!("Reloaded script {}", self.path.display());
+                    self.ast = Arc::new(ast);
+                }
+                Err(error) => eprintln!("{}, keeping the previous version", error),
+            }
+        }
+        self.ast.clone()
+    }
+}
+
+fn compile(engine: &Engine, path: &Path) -> Result<AST, ScriptError> {
+    let script = fs::read_to_string(path).map_err(|error| ScriptError(format!("{}: {}", path.display(), error)))?;
+    engine.compile(script).map_err(|error| ScriptError(format!("{}: {}", path.display(), error)))
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+impl ScriptStrategy {
+    fn new(source: Arc<Mutex<ScriptSource>>) -> Self {
+        let (engine, ast) = {
+            let mut source = source.lock().unwrap();
+            (source.engine.clone(), source.refresh())
+        };
+        let mut strategy = Self {
+            source,
+            engine,
+            ast,
+            state: Dynamic::from_map(Map::new()),
+            name: String::new(),
+            author: String::new(),
+        };
+        // A name or author function that fails gets the default, so a broken script can still be listed.
+        strategy.name = strategy.try_call::<ImmutableString>("name", ()).ok().flatten()
+            .map_or_else(|| "Script".to_string(), |name| name.to_string());
+        strategy.author = strategy.try_call::<ImmutableString>("author", ()).ok().flatten()
+            .map_or_else(|| "Unknown".to_string(), |author| author.to_string());
+        strategy
+    }
+
+    // None if the script doesn't define the function.
+    fn try_call<T: Clone + Send + Sync + 'static>(&mut self, function: &str, args: impl FuncArgs)
+                                                   -> Result<Option<T>, Box<EvalAltResult>> {
+        if !self.ast.iter_functions().any(|f| f.name == function) {
+            return Ok(None);
+        }
+        let options = CallFnOptions::new().eval_ast(false).bind_this_ptr(&mut self.state);
+        self.engine.call_fn_with_options(options, &mut Scope::new(), &self.ast, function, args).map(Some)
+    }
+
+    // A script error panics, so the strategy host records it as a fault.
+    fn call<T: Clone + Send + Sync + 'static>(&mut self, function: &str, args: impl FuncArgs) -> Option<T> {
+        match self.try_call(function, args) {
+            Ok(value) => value,
+            Err(error) => panic!("script {}: {}: {}", self.name, function, error),
+        }
+    }
+}
+
+impl Strategy for ScriptStrategy {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn author(&self) -> String {
+        self.author.clone()
+    }
+
+    fn next_move(&mut self, input: NextMoveInput) -> Move {
+        self.ast = self.source.lock().unwrap().refresh();
+        let input = rhai::serde::to_dynamic(&input).unwrap();
+        let next_move: ImmutableString = self.call("next_move", (input,))
+            .unwrap_or_else(|| panic!("script {}: no function next_move(input)", self.name));
+        match next_move.as_str() {
+            "Fire" => Move::Fire,
+            "TurnLeft" => Move::TurnLeft,
+            "Forward" => Move::Forward,
+            "TurnRight" => Move::TurnRight,
+            "Wait" => Move::Wait,
+            _ => panic!("script {}: next_move returned unknown move '{}'", self.name, next_move),
+        }
+    }
+
+    fn seed(&mut self, seed: u64) {
+        self.call::<Dynamic>("seed", (seed as i64,));
+    }
+
+    fn on_game_start(&mut self, info: &GameInfo) {
+        self.call::<Dynamic>("on_game_start", (rhai::serde::to_dynamic(info).unwrap(),));
+    }
+
+    fn on_round_start(&mut self, round: usize) {
+        self.call::<Dynamic>("on_round_start", (round as i64,));
+    }
+
+    fn on_round_end(&mut self, round: usize) {
+        self.call::<Dynamic>("on_round_end", (round as i64,));
+    }
+
+    fn on_hit(&mut self, by: usize) {
+        self.call::<Dynamic>("on_hit", (by as i64,));
+    }
+
+    fn on_frag(&mut self, tank: usize) {
+        self.call::<Dynamic>("on_frag", (tank as i64,));
+    }
+
+    fn on_game_end(&mut self, result: &MatchResult) {
+        self.call::<Dynamic>("on_game_end", (rhai::serde::to_dynamic(result).unwrap(),));
+    }
+}
+
+// Register a script from "ID=PATH". It is compiled once here and again whenever the file changes.
+pub(crate) fn register(registry: &mut Registry, script: &str) -> Result<(), ScriptError> {
+    let (id, path) = script.split_once('=')
+        .ok_or_else(|| ScriptError(format!("invalid script '{}', expected ID=PATH", script)))?;
+    let path = Path::new(path.trim());
+    let source = Arc::new(Mutex::new(ScriptSource::load(path)?));
+
+    let description = format!("Rhai script: {}", path.display());
+    registry.register(id.trim(), &description, move || -> Box<dyn Strategy> {
+        Box::new(ScriptStrategy::new(source.clone()))
+    });
+    Ok(())
+}
+
+#[derive(Debug)]
+pub(crate) struct ScriptError(String);
+
+impl Display for ScriptError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ScriptError {}
@@ -1,12 +1,28 @@
 
 use std::collections::VecDeque;
-use crate::game::{
+use rand::{
+    Rng,
+    SeedableRng
+};
+use rand::rngs::StdRng;
+use crate::engine::{
     BoardPoint,
     Direction,
     Move,
     NextMoveInput,
     Strategy
 };
+use crate::registry::Registry;
+
+pub(crate) const DEFAULT_ROSTER: [&str; 7] = ["dummy", "random", "slacker", "spinner", "firefire", "random", "random"];
+
+pub(crate) fn register(registry: &mut Registry) {
+    registry.register("dummy", "Repeats fire, turn left and forward", || Box::new(Dummy::default()));
+    registry.register("random", "Picks a random move every turn", || Box::new(Random::default()));
+    registry.register("slacker", "Always waits", || Box::new(Slacker::default()));
+    registry.register("spinner", "Alternates between firing and turning right", || Box::new(Spinner::default()));
+    registry.register("firefire", "Drives to the closest position where it can hit a living tank", || Box::new(FireFire::default()));
+}
 
 #[derive(Debug, Clone)]
 pub(crate) struct Dummy {
@@ -46,6 +62,7 @@ impl Strategy for Dummy {
 pub(crate) struct Random {
     name: String,
     author: String,
+    rng: StdRng,
 }
 
 impl Default for Random {
@@ -53,6 +70,7 @@ impl Default for Random {
         Self {
             name: "Random".to_string(),
             author: "Martin".to_string(),
+            rng: StdRng::from_entropy(),
         }
     }
 }
@@ -67,7 +85,11 @@ impl Strategy for Random {
     }
 
     fn next_move(&mut self, _input: NextMoveInput) -> Move {
-        rand::random()
+        self.rng.gen()
+    }
+
+    fn seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
     }
 }
 
@@ -182,7 +204,9 @@ impl Strategy for FireFire {
     }
 }
 
+// The lints in FireFire's search are allowed rather than rewritten, to keep the original code as it is.
 impl FireFire {
+    #[allow(clippy::too_many_arguments, clippy::len_zero)]
     fn find_move_to_closest_fire(&self, root: Position, search: Vec<Position>, mut visited: Vec<Position>,
                                  fire_range: usize, dead_positions: Vec<Position>, width: usize, height: usize) -> Move {
         let mut queue = VecDeque::new();
@@ -191,7 +215,6 @@ impl FireFire {
             let mut current_position = queue.pop_front().unwrap();
             visited.push(current_position.clone());
             if current_position.is_fire_position(&search, fire_range, &dead_positions) {
-                println!("visited.len(): {}", visited.len());
                 current_position.moves.push_back(Move::Fire);
                 return current_position.moves.pop_front().unwrap().clone();
             } else {
@@ -216,6 +239,7 @@ struct Position {
     moves: VecDeque<Move>,
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for Position {
     fn default() -> Self {
         Self {
@@ -249,6 +273,7 @@ impl Position {
         ]
     }
 
+    #[allow(clippy::ptr_arg, clippy::manual_contains)]
     fn fire(&self, fire_range: usize, dead_positions: &Vec<Position>) -> Vec<Position> {
         let mut positions = Vec::new();
         for i in 1..fire_range {
@@ -264,6 +289,7 @@ impl Position {
         }
         positions
     }
+    #[allow(clippy::ptr_arg, clippy::needless_return)]
     fn is_fire_position(&self, search: &Vec<Position>, fire_range: usize, dead_positions: &Vec<Position>) -> bool {
         let possible = self.fire(fire_range, dead_positions);
         for possible_position in possible {
@@ -0,0 +1,441 @@
+use std::collections::HashSet;
+use std::fmt::{
+    Display,
+    Formatter
+};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::atomic::{
+    AtomicUsize,
+    Ordering
+};
+use std::sync::Mutex;
+use std::thread;
+use crate::config::{
+    ConfigError,
+    MatchConfig
+};
+use crate::engine::MatchResult;
+use crate::ratings::Ratings;
+use crate::registry::{
+    Registry,
+    StrategyKind
+};
+use crate::replay::Recorder;
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Format {
+    // Every game is played by the whole roster.
+    FreeForAll,
+    // Every pair of roster entries plays the same number of one-on-one games.
+    RoundRobin,
+    // One-on-one games in rounds, pairing entries with similar scores that haven't met yet.
+    Swiss,
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::FreeForAll => write!(f, "free-for-all"),
+            Self::RoundRobin => write!(f, "round-robin"),
+            Self::Swiss => write!(f, "swiss"),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "free-for-all" | "ffa" => Ok(Self::FreeForAll),
+            "round-robin" => Ok(Self::RoundRobin),
+            "swiss" => Ok(Self::Swiss),
+            _ => Err(format!("unknown tournament format '{}', expected free-for-all, round-robin or swiss", value)),
+        }
+    }
+}
+
+// The roster of the match config are the participants, the rules and seed are used for every game.
+pub(crate) struct Tournament<'a> {
+    pub(crate) registry: &'a Registry,
+    pub(crate) config: MatchConfig,
+    pub(crate) format: Format,
+    // Games per pairing, or in total for free-for-all. Game n is played with the config seed plus n.
+    pub(crate) games: u64,
+    // Defaults to enough rounds to separate the participants.
+    pub(crate) swiss_rounds: Option<usize>,
+    pub(crate) threads: usize,
+    pub(crate) record_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Standing {
+    pub(crate) strategy: String,
+    pub(crate) games: usize,
+    pub(crate) wins: usize,
+    pub(crate) draws: usize,
+    placements: usize,
+    pub(crate) frags: usize,
+    pub(crate) hits: usize,
+}
+
+impl Standing {
+    pub(crate) fn average_placement(&self) -> f64 {
+        if self.games == 0 {
+            return 0.0;
+        }
+        self.placements as f64 / self.games as f64
+    }
+}
+
+struct Game {
+    number: usize,
+    participants: Vec<usize>,
+    seed: u64,
+}
+
+struct GameResult {
+    result: MatchResult,
+    frags: Vec<usize>,
+    hits: Vec<usize>,
+}
+
+impl Tournament<'_> {
+    // Standings are sorted best first: most wins, then most draws, then best average placement.
+    // Ratings are updated game by game in schedule order.
+    pub(crate) fn run(&self, mut ratings: Option<&mut Ratings>) -> Result<Vec<Standing>, TournamentError> {
+        self.config.validate(self.registry)?;
+        let participants = self.config.roster.len();
+        if self.format != Format::FreeForAll && participants < 2 {
+            return Err(TournamentError::TooFewParticipants(self.format));
+        }
+
+        let mut standings: Vec<Standing> = self.config.roster.iter()
+            .map(|entry| Standing {
+                strategy: entry.strategy.clone(),
+                games: 0,
+                wins: 0,
+                draws: 0,
+                placements: 0,
+                frags: 0,
+                hits: 0,
+            })
+            .collect();
+
+        match self.format {
+            Format::FreeForAll => {
+                let games = self.schedule(0, &[(0..participants).collect()]);
+                self.play_and_score(&games, &mut standings, ratings.as_deref_mut())?;
+            }
+            Format::RoundRobin => {
+                let pairings: Vec<Vec<usize>> = (0..participants)
+                    .flat_map(|first| (first + 1..participants).map(move |second| vec![first, second]))
+                    .collect();
+                let games = self.schedule(0, &pairings);
+                self.play_and_score(&games, &mut standings, ratings.as_deref_mut())?;
+            }
+            Format::Swiss => {
+                let rounds = self.swiss_rounds.unwrap_or_else(|| Self::default_swiss_rounds(participants));
+                let mut points = vec![0; participants];
+                let mut played = HashSet::new();
+                let mut number = 0;
+                for _ in 0..rounds {
+                    let (pairings, bye) = Self::swiss_pairings(&points, &played);
+                    // A bye counts as a won pairing.
+                    if let Some(participant) = bye {
+                        points[participant] += 2;
+                    }
+                    for pairing in &pairings {
+                        played.insert((pairing[0], pairing[1]));
+                        played.insert((pairing[1], pairing[0]));
+                    }
+                    let games = self.schedule(number, &pairings);
+                    number += games.len();
+                    let before = standings.clone();
+                    self.play_and_score(&games, &mut standings, ratings.as_deref_mut())?;
+                    // Wins are worth two points and draws one.
+                    for (participant, standing) in standings.iter().enumerate() {
+                        points[participant] += 2 * (standing.wins - before[participant].wins)
+                            + (standing.draws - before[participant].draws);
+                    }
+                }
+            }
+        }
+
+        standings.sort_by(|a, b| b.wins.cmp(&a.wins)
+            .then(b.draws.cmp(&a.draws))
+            .then(a.average_placement().total_cmp(&b.average_placement())));
+        Ok(standings)
+    }
+
+    fn default_swiss_rounds(participants: usize) -> usize {
+        (participants.next_power_of_two().trailing_zeros() as usize).max(1)
+    }
+
+    // Pair participants with similar points, without rematches if there is any way to. With an odd number of
+    // participants the lowest ranked one that still allows that gets a bye.
+    fn swiss_pairings(points: &[usize], played: &HashSet<(usize, usize)>) -> (Vec<Vec<usize>>, Option<usize>) {
+        let mut order: Vec<usize> = (0..points.len()).collect();
+        order.sort_by_key(|&participant| std::cmp::Reverse(points[participant]));
+
+        let byes: Vec<Option<usize>> = if order.len() % 2 == 1 {
+            order.iter().rev().map(|&participant| Some(participant)).collect()
+        } else {
+            vec![None]
+        };
+        for &bye in &byes {
+            let rest: Vec<usize> = order.iter().copied().filter(|&participant| Some(participant) != bye).collect();
+            if let Some(pairings) = Self::pair_without_rematches(&rest, played) {
+                return (pairings, bye);
+            }
+        }
+
+        // Rematches can't be avoided, pair the best ranked ones that haven't met.
+        let mut pairings = Vec::new();
+        while order.len() > 1 {
+            let first = order.remove(0);
+            let opponent = order.iter().position(|&other| !played.contains(&(first, other))).unwrap_or(0);
+            let second = order.remove(opponent);
+            pairings.push(vec![first, second]);
+        }
+        (pairings, order.pop())
+    }
+
+    // Pair the first participant with the best ranked one it hasn't met that lets the rest be paired as well.
+    fn pair_without_rematches(order: &[usize], played: &HashSet<(usize, usize)>) -> Option<Vec<Vec<usize>>> {
+        let Some((&first, rest)) = order.split_first() else {
+            return Some(Vec::new());
+        };
+        for (position, &second) in rest.iter().enumerate() {
+            if played.contains(&(first, second)) {
+                continue;
+            }
+            let mut others = rest.to_vec();
+            others.remove(position);
+            if let Some(mut pairings) = Self::pair_without_rematches(&others, played) {
+                pairings.insert(0, vec![first, second]);
+                return Some(pairings);
+            }
+        }
+        None
+    }
+
+    fn schedule(&self, first_number: usize, pairings: &[Vec<usize>]) -> Vec<Game> {
+        pairings.iter()
+            .flat_map(|participants| (0..self.games).map(move |game| (participants, game)))
+            .enumerate()
+            .map(|(index, (participants, game))| Game {
+                number: first_number + index,
+                participants: participants.clone(),
+                seed: self.config.seed.wrapping_add(game),
+            })
+            .collect()
+    }
+
+    fn play_and_score(&self, games: &[Game], standings: &mut [Standing], mut ratings: Option<&mut Ratings>)
+                      -> Result<(), TournamentError> {
+        for (game, game_result) in games.iter().zip(self.play(games)?) {
+            let result = &game_result.result;
+            if let Some(ratings) = ratings.as_deref_mut() {
+                let placements: Vec<(&str, usize)> = result.ranking.iter()
+                    .map(|placement| {
+                        let entry = &self.config.roster[game.participants[placement.tank]];
+                        (self.registry.lookup(&entry.strategy).unwrap().id.as_str(), placement.place)
+                    })
+                    .collect();
+                ratings.update(&placements);
+            }
+            for placement in &result.ranking {
+                let standing = &mut standings[game.participants[placement.tank]];
+                standing.games += 1;
+                if result.winner == Some(placement.tank) {
+                    standing.wins += 1;
+                } else if result.winner.is_none() && placement.eliminated.is_none() {
+                    standing.draws += 1;
+                }
+                standing.placements += placement.place;
+                standing.frags += game_result.frags[placement.tank];
+                standing.hits += game_result.hits[placement.tank];
+            }
+        }
+        Ok(())
+    }
+
+    fn play(&self, games: &[Game]) -> Result<Vec<GameResult>, TournamentError> {
+        // A remote bot plays one game at a time.
+        let mut kinds = self.registry.kinds(self.config.roster.iter().map(|entry| &entry.strategy));
+        let threads = if kinds.any(|kind| kind == StrategyKind::Remote) { 1 } else { self.threads };
+        play_parallel(games, threads, |game| self.play_game(game)).into_iter().collect()
+    }
+
+    fn play_game(&self, game: &Game) -> Result<GameResult, TournamentError> {
+        let config = MatchConfig {
+            rules: self.config.rules.clone(),
+            roster: game.participants.iter().map(|&participant| self.config.roster[participant].clone()).collect(),
+            seed: game.seed,
+        };
+        let mut engine = config.create_engine(self.registry)?;
+        match &self.record_dir {
+            Some(dir) => {
+                let path = dir.join(format!("game-{}-{}.jsonl", game.number, game.seed));
+                Recorder::create(&path, &config, &engine)
+                    .and_then(|mut recorder| recorder.run_to_completion(&mut engine))
+                    .map_err(|error| TournamentError::Record(path, error.to_string()))?;
+            }
+            None => {
+                engine.run_to_completion();
+            }
+        }
+        Ok(GameResult {
+            result: engine.result().unwrap().clone(),
+            frags: engine.tanks().iter().map(|tank| tank.frags).collect(),
+            hits: engine.tanks().iter().map(|tank| tank.hits).collect(),
+        })
+    }
+}
+
+// Worker threads take the next unplayed job until all are done. Results are in job order.
+pub(crate) fn play_parallel<T, R, F>(jobs: &[T], threads: usize, play: F) -> Vec<R>
+    where
+        T: Sync,
+        R: Send,
+        F: Fn(&T) -> R + Sync
+{
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<R>>> = Mutex::new((0..jobs.len()).map(|_| None).collect());
+    thread::scope(|scope| {
+        for _ in 0..threads.clamp(1, jobs.len().max(1)) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(job) = jobs.get(index) else {
+                    break;
+                };
+                let result = play(job);
+                results.lock().unwrap()[index] = Some(result);
+            });
+        }
+    });
+    results.into_inner().unwrap().into_iter()
+        .map(|result| result.expect("every job is played"))
+        .collect()
+}
+
+#[derive(Debug)]
+pub(crate) enum TournamentError {
+    Config(ConfigError),
+    TooFewParticipants(Format),
+    Record(PathBuf, String),
+}
+
+impl From<ConfigError> for TournamentError {
+    fn from(error: ConfigError) -> Self {
+        Self::Config(error)
+    }
+}
+
+impl Display for TournamentError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Config(error) => write!(f, "{}", error),
+            Self::TooFewParticipants(format) => write!(f, "a {} tournament needs at least two strategies", format),
+            Self::Record(path, error) => write!(f, "can't record to {}: {}", path.display(), error),
+        }
+    }
+}
+
+impl std::error::Error for TournamentError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RosterEntry;
+    use crate::engine::Rules;
+
+    // Plays Swiss rounds where the participant with the lower index always wins.
+    fn swiss_rounds(participants: usize, rounds: usize) -> Vec<(Vec<Vec<usize>>, Option<usize>)> {
+        let mut points = vec![0; participants];
+        let mut played = HashSet::new();
+        let mut history = Vec::new();
+        for _ in 0..rounds {
+            let (pairings, bye) = Tournament::swiss_pairings(&points, &played);
+            if let Some(participant) = bye {
+                points[participant] += 2;
+            }
+            for pairing in &pairings {
+                played.insert((pairing[0], pairing[1]));
+                played.insert((pairing[1], pairing[0]));
+                points[pairing[0].min(pairing[1])] += 2;
+            }
+            history.push((pairings, bye));
+        }
+        history
+    }
+
+    #[test]
+    fn swiss_pairs_by_points() {
+        let played = HashSet::new();
+        let (pairings, bye) = Tournament::swiss_pairings(&[0, 4, 2, 4], &played);
+        assert_eq!(pairings, vec![vec![1, 3], vec![2, 0]]);
+        assert_eq!(bye, None);
+    }
+
+    #[test]
+    fn swiss_avoids_rematches() {
+        for participants in 2..=16 {
+            let rounds = Tournament::default_swiss_rounds(participants);
+            let mut pairs = HashSet::new();
+            for (pairings, _) in swiss_rounds(participants, rounds) {
+                for pairing in pairings {
+                    let pair = (pairing[0].min(pairing[1]), pairing[0].max(pairing[1]));
+                    assert!(pairs.insert(pair), "{:?} met twice with {} participants", pair, participants);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn swiss_rematch_only_when_unavoidable() {
+        let played = HashSet::from([(0, 1), (1, 0)]);
+        let (pairings, bye) = Tournament::swiss_pairings(&[2, 0], &played);
+        assert_eq!(pairings, vec![vec![0, 1]]);
+        assert_eq!(bye, None);
+    }
+
+    #[test]
+    fn swiss_bye_goes_to_the_lowest_ranked() {
+        let played = HashSet::new();
+        let (pairings, bye) = Tournament::swiss_pairings(&[2, 0, 4], &played);
+        assert_eq!(pairings, vec![vec![2, 0]]);
+        assert_eq!(bye, Some(1));
+    }
+
+    #[test]
+    fn schedule_numbers_games_and_repeats_seeds_per_pairing() {
+        let registry = Registry::builtin();
+        let tournament = Tournament {
+            registry: &registry,
+            config: MatchConfig {
+                rules: Rules::default(),
+                roster: vec![RosterEntry::new("random"), RosterEntry::new("dummy"), RosterEntry::new("slacker")],
+                seed: 10,
+            },
+            format: Format::RoundRobin,
+            games: 2,
+            swiss_rounds: None,
+            threads: 1,
+            record_dir: None,
+        };
+        let games = tournament.schedule(5, &[vec![0, 1], vec![1, 2]]);
+        let schedule: Vec<(usize, Vec<usize>, u64)> = games.into_iter()
+            .map(|game| (game.number, game.participants, game.seed))
+            .collect();
+        assert_eq!(schedule, vec![
+            (5, vec![0, 1], 10),
+            (6, vec![0, 1], 11),
+            (7, vec![1, 2], 10),
+            (8, vec![1, 2], 11),
+        ]);
+    }
+}
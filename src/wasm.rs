@@ -0,0 +1,219 @@
+use std::fmt::{
+    Display,
+    Formatter
+};
+use std::fs;
+use std::path::Path;
+use wasmi::{
+    Config,
+    Instance,
+    Linker,
+    Memory,
+    Module,
+    Store,
+    StoreLimits,
+    StoreLimitsBuilder,
+    TypedFunc
+};
+use crate::engine::{
+    Move,
+    NextMoveInput,
+    Strategy
+};
+use crate::registry::{
+    FailedStrategy,
+    Registry
+};
+
+
+// A strategy compiled to WebAssembly, run in the wasmi interpreter. The module can't import anything,
+// so all it can do is compute. Its exports:
+//
+//   memory                                  the linear memory
+//   alloc(len: i32) -> i32                  reserve len bytes for the input and return their offset
+//   next_move(ptr: i32, len: i32) -> i32    read NextMoveInput as JSON from memory and return the move:
+//                                           0 Fire, 1 TurnLeft, 2 Forward, 3 TurnRight, 4 Wait
+//   name() -> i64, author() -> i64          optional, offset << 32 | len of a UTF-8 string in memory
+//   seed(seed: i64)                         optional
+//
+// Every call gets a fresh fuel budget. Running out of fuel, using too much memory or any other trap
+// panics, so the strategy host records it as a fault.
+pub(crate) struct WasmStrategy {
+    store: Store<StoreLimits>,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    next_move: TypedFunc<(i32, i32), i32>,
+    seed: Option<TypedFunc<i64, ()>>,
+    name: String,
+    author: String,
+    fuel: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct WasmLimits {
+    // Roughly the number of instructions one call may execute.
+    pub(crate) fuel: u64,
+    pub(crate) memory_bytes: usize,
+}
+
+impl Default for WasmLimits {
+    fn default() -> Self {
+        Self {
+            fuel: 10_000_000,
+            memory_bytes: 16 * 1024 * 1024,
+        }
+    }
+}
+
+// A compiled module that can be instantiated for every game.
+pub(crate) struct WasmModule {
+    engine: wasmi::Engine,
+    module: Module,
+    limits: WasmLimits,
+}
+
+impl WasmModule {
+    // Files ending in .wat are read as WebAssembly text, everything else as binary.
+    pub(crate) fn load(path: &Path, limits: WasmLimits) -> Result<Self, WasmError> {
+        let error = |message: String| WasmError(format!("{}: {}", path.display(), message));
+        let bytes = fs::read(path).map_err(|e| error(e.to_string()))?;
+        let is_text = path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("wat"));
+        let bytes = if is_text { wat::parse_bytes(&bytes).map_err(|e| error(e.to_string()))?.into_owned() } else { bytes };
+
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = wasmi::Engine::new(&config);
+        let module = Module::new(&engine, &bytes).map_err(|e| error(e.to_string()))?;
+        if let Some(import) = module.imports().next() {
+            return Err(error(format!("imports are not allowed, found {}::{}", import.module(), import.name())));
+        }
+        Ok(Self { engine, module, limits })
+    }
+
+    pub(crate) fn instantiate(&self) -> Result<WasmStrategy, WasmError> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(self.limits.memory_bytes)
+            .build();
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits| limits);
+        store.set_fuel(self.limits.fuel).map_err(|error| WasmError(error.to_string()))?;
+
+        let error = |message: String| WasmError(message);
+        let instance = Linker::<StoreLimits>::new(&self.engine)
+            .instantiate(&mut store, &self.module)
+            .and_then(|instance| instance.start(&mut store))
+            .map_err(|e| error(e.to_string()))?;
+        let memory = instance.get_memory(&store, "memory").ok_or_else(|| error("no exported memory".to_string()))?;
+        let alloc = instance.get_typed_func(&store, "alloc").map_err(|e| error(format!("alloc: {}", e)))?;
+        let next_move = instance.get_typed_func(&store, "next_move").map_err(|e| error(format!("next_move: {}", e)))?;
+        let seed = instance.get_typed_func(&store, "seed").ok();
+
+        let mut strategy = WasmStrategy {
+            store,
+            memory,
+            alloc,
+            next_move,
+            seed,
+            name: String::new(),
+            author: String::new(),
+            fuel: self.limits.fuel,
+        };
+        strategy.name = strategy.read_string(&instance, "name")?.unwrap_or_else(|| "Wasm".to_string());
+        strategy.author = strategy.read_string(&instance, "author")?.unwrap_or_else(|| "Unknown".to_string());
+        Ok(strategy)
+    }
+}
+
+impl WasmStrategy {
+    const MAX_STRING_LEN: usize = 1024;
+
+    fn read_string(&mut self, instance: &Instance, export: &str) -> Result<Option<String>, WasmError> {
+        let Ok(function) = instance.get_typed_func::<(), i64>(&self.store, export) else {
+            return Ok(None);
+        };
+        self.store.set_fuel(self.fuel).map_err(|error| WasmError(error.to_string()))?;
+        let packed = function.call(&mut self.store, ()).map_err(|error| WasmError(format!("{}: {}", export, error)))?;
+        let (offset, len) = ((packed as u64 >> 32) as usize, (packed as u64 & 0xFFFF_FFFF) as usize);
+        // The module is untrusted, check the string is in its memory before copying it.
+        if len > Self::MAX_STRING_LEN {
+            return Err(WasmError(format!("{}: string of {} bytes is longer than {}", export, len, Self::MAX_STRING_LEN)));
+        }
+        let bytes = self.memory.data(&self.store).get(offset..offset + len)
+            .ok_or_else(|| WasmError(format!("{}: string at {} of {} bytes is outside the memory", export, offset, len)))?;
+        String::from_utf8(bytes.to_vec()).map(Some).map_err(|error| WasmError(format!("{}: {}", export, error)))
+    }
+
+    fn call_next_move(&mut self, input: &NextMoveInput) -> Result<Move, String> {
+        let json = serde_json::to_vec(input).unwrap();
+        self.store.set_fuel(self.fuel).map_err(|error| error.to_string())?;
+        let ptr = self.alloc.call(&mut self.store, json.len() as i32).map_err(|error| format!("alloc: {}", error))?;
+        self.memory.write(&mut self.store, ptr as usize, &json).map_err(|error| format!("alloc: {}", error))?;
+        let code = self.next_move.call(&mut self.store, (ptr, json.len() as i32))
+            .map_err(|error| format!("next_move: {}", error))?;
+        match code {
+            0 => Ok(Move::Fire),
+            1 => Ok(Move::TurnLeft),
+            2 => Ok(Move::Forward),
+            3 => Ok(Move::TurnRight),
+            4 => Ok(Move::Wait),
+            _ => Err(format!("next_move returned unknown move {}", code)),
+        }
+    }
+}
+
+impl Strategy for WasmStrategy {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn author(&self) -> String {
+        self.author.clone()
+    }
+
+    fn next_move(&mut self, input: NextMoveInput) -> Move {
+        match self.call_next_move(&input) {
+            Ok(next_move) => next_move,
+            Err(error) => panic!("wasm strategy {}: {}", self.name, error),
+        }
+    }
+
+    fn seed(&mut self, seed: u64) {
+        if let Some(function) = self.seed {
+            self.store.set_fuel(self.fuel).unwrap();
+            if let Err(error) = function.call(&mut self.store, seed as i64) {
+                panic!("wasm strategy {}: seed: {}", self.name, error);
+            }
+        }
+    }
+}
+
+// Register a module from "ID=PATH". It is instantiated once to check its exports.
+pub(crate) fn register(registry: &mut Registry, plugin: &str, limits: WasmLimits) -> Result<(), WasmError> {
+    let (id, path) = plugin.split_once('=')
+        .ok_or_else(|| WasmError(format!("invalid wasm plugin '{}', expected ID=PATH", plugin)))?;
+    let path = Path::new(path.trim());
+    let module = WasmModule::load(path, limits)?;
+    let probe = module.instantiate().map_err(|error| WasmError(format!("{}: {}", path.display(), error)))?;
+    let (name, author) = (probe.name(), probe.author());
+
+    let description = format!("WebAssembly: {}", path.display());
+    registry.register(id.trim(), &description, move || -> Box<dyn Strategy> {
+        // Instantiation worked once, so it only fails if the start function behaves differently.
+        match module.instantiate() {
+            Ok(strategy) => Box::new(strategy),
+            Err(error) => Box::new(FailedStrategy::new(&name, &author, &format!("wasm strategy {}: {}", name, error))),
+        }
+    });
+    Ok(())
+}
+
+#[derive(Debug)]
+pub(crate) struct WasmError(String);
+
+impl Display for WasmError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for WasmError {}